@@ -0,0 +1,119 @@
+//! Benchmarks of the targeting, collision and movement systems at growing entity counts,
+//! each run on its own against a headless world.
+
+use bevy::{
+    ecs::system::{CommandQueue, System},
+    prelude::*,
+};
+use bevy_tower_defense::*;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+const COUNTS: [usize; 3] = [100, 1_000, 10_000];
+
+/// Distance between two targets of the benchmark wave, as in the stress test.
+const SPACING: f32 = 0.5;
+
+fn path_start() -> Vec3 {
+    Level::default().path_start
+}
+
+/// A headless world with `count` targets queued behind the start of the path, like a wave, and
+/// `count` bullets crossing the path, every other one through a target.
+fn world(count: usize) -> App {
+    let mut app = App::new();
+    app.insert_resource(GameRng::new(0)).add_plugin(HeadlessPlugin);
+    let assets = GameAssets::placeholder();
+    let mut pool = app.world.remove_resource::<ProjectilePool>().unwrap();
+    let mut queue = CommandQueue::default();
+    {
+        let mut commands = Commands::new(&mut queue, &app.world);
+        for i in 0..count {
+            let position = path_start() - Vec3::X * SPACING * i as f32;
+            let target = Target {
+                speed: TARGET_SPEED,
+                bounty: TARGET_BOUNTY,
+                wave: 0,
+                radius: TARGET_RADIUS,
+            };
+            spawn_target(&mut commands, &assets, position, target, Health { value: TARGET_HEALTH });
+
+            let tower_type = TowerType::ALL[i % TowerType::ALL.len()];
+            let (scene, mut bullet) = tower_type.get_bullet(Vec3::Z, &assets);
+            bullet.travelled = Vec3::Z * bullet.speed * SimulationClock::delta_seconds();
+            let miss = if i % 2 == 0 { 0.0 } else { 3.0 };
+            let translation = position + Vec3::Z * miss;
+            spawn_bullet(&mut commands, &mut pool, tower_type, scene, translation, bullet);
+        }
+    }
+    queue.apply(&mut app.world);
+    app.insert_resource(pool);
+    run_once(&mut app.world, index_targets);
+    app
+}
+
+fn run_once<Params>(world: &mut World, system: impl IntoSystem<(), (), Params>) {
+    let mut system = IntoSystem::into_system(system);
+    system.initialize(world);
+    system.run((), world);
+    system.apply_buffers(world);
+}
+
+/// Runs `system` on a fresh world of each size, timing the system and its commands only.
+fn bench_system<Params, S>(c: &mut Criterion, name: &str, system: fn() -> S)
+where
+    S: IntoSystem<(), (), Params>,
+{
+    let mut group = c.benchmark_group(name);
+    for count in COUNTS {
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+            b.iter_batched(
+                || {
+                    let mut app = world(count);
+                    let mut system = IntoSystem::into_system(system());
+                    system.initialize(&mut app.world);
+                    (app, system)
+                },
+                |(mut app, mut system)| {
+                    system.run((), &mut app.world);
+                    system.apply_buffers(&mut app.world);
+                    app
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn get_direction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_direction");
+    for count in COUNTS {
+        let app = world(count);
+        let grid = app.world.resource::<TargetGrid>();
+        let (_, tower) = TowerType::Tomato.get_tower(&GameAssets::placeholder());
+        // A slot halfway along the wave, as far from the path as the level's slots are.
+        let transform = Transform::from_translation(
+            path_start() - Vec3::X * SPACING * count as f32 / 2.0 + Vec3::new(0.0, 0.4, 5.5),
+        );
+        let shooter = TowerShooter::new(Entity::from_raw(0), &TowerType::Tomato, &transform);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| shooter.get_direction(&tower, grid))
+        });
+    }
+    group.finish();
+}
+
+fn bullet_collision(c: &mut Criterion) {
+    bench_system(c, "bullet_collision", || bevy_tower_defense::bullet_collision);
+}
+
+fn move_bullets(c: &mut Criterion) {
+    bench_system(c, "move_bullets", || bevy_tower_defense::move_bullets);
+}
+
+fn move_targets(c: &mut Criterion) {
+    bench_system(c, "move_targets", || bevy_tower_defense::move_targets);
+}
+
+criterion_group!(benches, get_direction, bullet_collision, move_bullets, move_targets);
+criterion_main!(benches);
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// A build slot, identified by its index in the level so actions can refer to it
+/// across runs (entity ids are not stable).
+#[derive(Reflect, Component, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub struct BuildSlot(pub usize);
+
+/// Everything the player can do that changes the game. Actions are queued from input
+/// and applied at the start of the next simulation tick, so they can be recorded and replayed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PlayerAction {
+    BuildTower { slot: usize, tower_type: TowerType },
+}
+
+/// Actions waiting for the next simulation tick.
+#[derive(Default, Debug)]
+pub struct ActionQueue(Vec<PlayerAction>);
+
+impl ActionQueue {
+    pub fn push(&mut self, action: PlayerAction) {
+        self.0.push(action);
+    }
+}
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<BuildSlot>()
+            .init_resource::<ActionQueue>()
+            .add_simulation_system(SimulationSet::Input, apply_player_actions)
+            .add_system_to_stage(CoreStage::Last, flush_replay);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Spawns an empty build slot. The windowed game decorates it with a mesh and picking.
+pub fn spawn_build_slot(commands: &mut Commands, position: Vec3, slot: usize) -> Entity {
+    commands
+        .spawn_bundle(SpatialBundle::from_transform(Transform::from_translation(
+            position,
+        )))
+        .insert(Name::new("Tower_Base"))
+        .insert(BuildSlot(slot))
+        .id()
+}
+
+fn apply_player_actions(
+    mut commands: Commands,
+    mut queue: ResMut<ActionQueue>,
+    (playback, recorder): (Option<ResMut<ReplayPlayback>>, Option<ResMut<ReplayRecorder>>),
+    mut player: ResMut<Player>,
+    clock: Res<SimulationClock>,
+    slots: Query<(Entity, &BuildSlot, &Transform), Without<Tower>>,
+    assets: Res<GameAssets>,
+) {
+    let actions = match playback {
+        Some(mut playback) => {
+            // Live input is ignored while a replay drives the game.
+            queue.0.clear();
+            playback.take_due(clock.tick())
+        }
+        None => std::mem::take(&mut queue.0),
+    };
+    if actions.is_empty() {
+        return;
+    }
+    if let Some(mut recorder) = recorder {
+        recorder.record(clock.tick(), &actions);
+    }
+    // Despawns only take effect after the tick, so remember the slots built on this tick.
+    let mut built = Vec::new();
+    for action in actions {
+        match action {
+            PlayerAction::BuildTower { slot, tower_type } => {
+                if player.gold < tower_type.cost() {
+                    warn!("Cannot afford a {:?} tower on slot {}", tower_type, slot);
+                    continue;
+                }
+                let free_slot = slots
+                    .iter()
+                    .find(|(_, build_slot, _)| build_slot.0 == slot && !built.contains(&slot));
+                match free_slot {
+                    Some((entity, build_slot, transform)) => {
+                        built.push(slot);
+                        player.gold -= tower_type.cost();
+                        //Remove the base model/hitbox
+                        commands.entity(entity).despawn_recursive();
+                        let tower =
+                            spawn_tower(&mut commands, &assets, transform.translation, tower_type);
+                        commands.entity(tower).insert(*build_slot);
+                    }
+                    None => warn!("Cannot build on slot {}, it is taken or missing", slot),
+                }
+            }
+        }
+    }
+}
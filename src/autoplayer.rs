@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::*;
+
+/// Ticks between two decisions of the autoplayer.
+pub const AUTOPLAYER_INTERVAL: u64 = TICK_RATE as u64 / 2;
+
+/// Towers shoot at any range, but bullets are slow and miss moving targets,
+/// so path close to a slot is what counts as covered.
+pub const COVERAGE_RADIUS: f32 = 6.0;
+
+/// What a strategy knows about the game when it decides.
+pub struct AutoPlayerView<'a> {
+    pub gold: u32,
+    pub level: &'a Level,
+    /// Indices of the build slots without a tower, in ascending order.
+    pub free_slots: &'a [usize],
+    /// Slot and type of every built tower, by ascending slot.
+    pub towers: &'a [(usize, TowerType)],
+}
+
+/// Decides what the autoplayer does. Strategies only issue `PlayerAction`s,
+/// which go through the same queue, rules and replay recording as the player's input.
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Actions to queue now. `rng` is the autoplayer's own stream of the game seed.
+    fn decide(&mut self, view: &AutoPlayerView, rng: &mut ChaCha8Rng) -> Vec<PlayerAction>;
+}
+
+/// Plays the game with a strategy while this resource exists.
+pub struct AutoPlayer {
+    strategy: Box<dyn Strategy>,
+}
+
+impl AutoPlayer {
+    pub fn new(strategy: impl Strategy + 'static) -> Self {
+        AutoPlayer {
+            strategy: Box::new(strategy),
+        }
+    }
+
+    /// The built-in strategy called `name`: `greedy` or `random`.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "greedy" => Ok(AutoPlayer::new(GreedyCoverage)),
+            "random" => Ok(AutoPlayer::new(RandomStrategy)),
+            _ => Err(format!(
+                "unknown autoplayer strategy '{}' (expected greedy or random)",
+                name
+            )),
+        }
+    }
+
+    pub fn strategy_name(&self) -> &str {
+        self.strategy.name()
+    }
+}
+
+pub struct AutoPlayerPlugin;
+
+impl Plugin for AutoPlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_system(SimulationSet::Input, run_autoplayer);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn run_autoplayer(
+    autoplayer: Option<ResMut<AutoPlayer>>,
+    mut queue: ResMut<ActionQueue>,
+    mut rng: ResMut<GameRng>,
+    player: Res<Player>,
+    level: Res<Level>,
+    clock: Res<SimulationClock>,
+    slots: Query<(&BuildSlot, Option<&TowerType>)>,
+) {
+    let mut autoplayer = match autoplayer {
+        Some(autoplayer) => autoplayer,
+        None => return,
+    };
+    if clock.tick() % AUTOPLAYER_INTERVAL != 1 {
+        return;
+    }
+    let mut free_slots = Vec::new();
+    let mut built = Vec::new();
+    for (slot, tower_type) in &slots {
+        match tower_type {
+            Some(tower_type) => built.push((slot.0, *tower_type)),
+            None => free_slots.push(slot.0),
+        }
+    }
+    free_slots.sort_unstable();
+    built.sort_unstable_by_key(|(slot, _)| *slot);
+
+    let view = AutoPlayerView {
+        gold: player.gold,
+        level: &level,
+        free_slots: &free_slots,
+        towers: &built,
+    };
+    let actions = autoplayer
+        .strategy
+        .decide(&view, rng.stream(RngStream::AutoPlayer));
+    for action in actions {
+        queue.push(action);
+    }
+}
+
+/// Length of the path within `COVERAGE_RADIUS` of a position.
+pub fn path_coverage(level: &Level, position: Vec3) -> f32 {
+    let offset = (position.z - level.path_start.z).abs();
+    if offset >= COVERAGE_RADIUS {
+        return 0.0;
+    }
+    let half_width = (COVERAGE_RADIUS * COVERAGE_RADIUS - offset * offset).sqrt();
+    let start = (position.x - half_width).max(level.path_start.x);
+    let end = (position.x + half_width).min(level.path_end);
+    (end - start).max(0.0)
+}
+
+/// Saves up for the tower with the most shots per second per gold and builds it
+/// on the free slot that covers the most path.
+pub struct GreedyCoverage;
+
+impl GreedyCoverage {
+    fn best_value() -> TowerType {
+        TowerType::ALL
+            .into_iter()
+            .max_by_key(|tower_type| {
+                FloatOrd(1.0 / (tower_type.shot_interval() * tower_type.cost() as f32))
+            })
+            .unwrap()
+    }
+}
+
+impl Strategy for GreedyCoverage {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn decide(&mut self, view: &AutoPlayerView, _rng: &mut ChaCha8Rng) -> Vec<PlayerAction> {
+        let tower_type = Self::best_value();
+        if view.gold < tower_type.cost() {
+            return Vec::new();
+        }
+        // `max_by_key` keeps the last maximum, so iterate backwards to prefer the lowest slot on ties.
+        let best_slot = view.free_slots.iter().rev().max_by_key(|slot| {
+            FloatOrd(path_coverage(view.level, view.level.slots[**slot]))
+        });
+        match best_slot {
+            Some(slot) => vec![PlayerAction::BuildTower {
+                slot: *slot,
+                tower_type,
+            }],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Builds a random affordable tower on a random free slot.
+pub struct RandomStrategy;
+
+impl Strategy for RandomStrategy {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn decide(&mut self, view: &AutoPlayerView, rng: &mut ChaCha8Rng) -> Vec<PlayerAction> {
+        let affordable: Vec<TowerType> = TowerType::ALL
+            .into_iter()
+            .filter(|tower_type| tower_type.cost() <= view.gold)
+            .collect();
+        if affordable.is_empty() || view.free_slots.is_empty() || !rng.gen_bool(0.5) {
+            return Vec::new();
+        }
+        vec![PlayerAction::BuildTower {
+            slot: *view.free_slots.choose(rng).unwrap(),
+            tower_type: *affordable.choose(rng).unwrap(),
+        }]
+    }
+}
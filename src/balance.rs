@@ -0,0 +1,217 @@
+use std::{collections::VecDeque, fmt::Write, fs, path::PathBuf};
+
+use bevy::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PlannedTower {
+    pub slot: usize,
+    pub tower_type: TowerType,
+}
+
+/// Towers to build in order, each as soon as there is gold for it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PlacementPlan {
+    pub towers: Vec<PlannedTower>,
+}
+
+impl PlacementPlan {
+    pub fn load(path: &PathBuf) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        ron::from_str(&source).map_err(|err| err.to_string())
+    }
+}
+
+/// Autoplayer strategy following a placement plan.
+pub struct PlanStrategy {
+    remaining: VecDeque<PlannedTower>,
+}
+
+impl PlanStrategy {
+    pub fn new(plan: &PlacementPlan) -> Self {
+        PlanStrategy {
+            remaining: plan.towers.iter().copied().collect(),
+        }
+    }
+}
+
+impl Strategy for PlanStrategy {
+    fn name(&self) -> &str {
+        "plan"
+    }
+
+    fn decide(&mut self, view: &AutoPlayerView, _rng: &mut ChaCha8Rng) -> Vec<PlayerAction> {
+        let mut gold = view.gold;
+        let mut actions = Vec::new();
+        while let Some(next) = self.remaining.front() {
+            if !view.free_slots.contains(&next.slot) {
+                warn!("Plan builds on slot {}, which is taken or missing", next.slot);
+                self.remaining.pop_front();
+                continue;
+            }
+            if gold < next.tower_type.cost() {
+                break;
+            }
+            gold -= next.tower_type.cost();
+            actions.push(PlayerAction::BuildTower {
+                slot: next.slot,
+                tower_type: next.tower_type,
+            });
+            self.remaining.pop_front();
+        }
+        actions
+    }
+}
+
+/// Everything that decides the outcome of a balance run.
+#[derive(Debug, Clone)]
+pub struct BalanceRun {
+    pub level: Level,
+    pub plan: PlacementPlan,
+    pub difficulty: Difficulty,
+    pub seed: u64,
+    /// Simulated seconds after which a level that is still not cleared is given up on.
+    pub max_seconds: f64,
+    /// Gold to start with instead of the difficulty's starting gold.
+    pub starting_gold: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TowerReport {
+    pub slot: usize,
+    pub tower_type: TowerType,
+    pub shots_fired: u32,
+    pub hits: u32,
+    pub damage: u32,
+    /// Damage per second since the tower was built.
+    pub dps: f64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WaveReport {
+    pub wave: usize,
+    pub spawned: u32,
+    pub killed: u32,
+    pub leaked: u32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BalanceReport {
+    pub level: String,
+    pub seed: u64,
+    pub cleared: bool,
+    pub total_seconds: f64,
+    pub gold: u32,
+    pub lives: u32,
+    pub towers: Vec<TowerReport>,
+    pub waves: Vec<WaveReport>,
+}
+
+impl BalanceRun {
+    /// Simulates the level headlessly as fast as possible and collects the metrics.
+    pub fn simulate(&self) -> BalanceReport {
+        let mut app = App::new();
+        app.insert_resource(self.level.clone())
+            .insert_resource(self.difficulty)
+            .insert_resource(GameRng::new(self.seed))
+            .insert_resource(AutoPlayer::new(PlanStrategy::new(&self.plan)))
+            .add_plugin(HeadlessPlugin)
+            .add_startup_system(spawn_headless_scene);
+        if let Some(gold) = self.starting_gold {
+            app.add_startup_system(move |mut player: ResMut<Player>| player.gold = gold);
+        }
+
+        let max_ticks = (self.max_seconds * TICK_RATE as f64).ceil() as u64;
+        loop {
+            app.update();
+            let tick = app.world.resource::<SimulationClock>().tick();
+            if app.world.resource::<WaveState>().is_cleared() || tick >= max_ticks {
+                break;
+            }
+        }
+        self.report(&mut app.world)
+    }
+
+    fn report(&self, world: &mut World) -> BalanceReport {
+        // The run stops on the update the level is cleared, one tick per update.
+        let cleared = world.resource::<WaveState>().is_cleared();
+        let total_seconds = world.resource::<SimulationClock>().elapsed_seconds();
+
+        let mut towers: Vec<TowerReport> = world
+            .query::<(&BuildSlot, &TowerType, &TowerStats)>()
+            .iter(world)
+            .map(|(slot, tower_type, stats)| TowerReport {
+                slot: slot.0,
+                tower_type: *tower_type,
+                shots_fired: stats.shots_fired,
+                hits: stats.hits,
+                damage: stats.damage,
+                dps: stats.dps(),
+            })
+            .collect();
+        towers.sort_by_key(|tower| tower.slot);
+
+        let stats = world.resource::<GameStats>();
+        let waves = (0..self.level.waves.len())
+            .map(|wave| {
+                let stats = stats.waves.get(wave).copied().unwrap_or_default();
+                WaveReport {
+                    wave,
+                    spawned: stats.spawned,
+                    killed: stats.killed,
+                    leaked: stats.leaked,
+                }
+            })
+            .collect();
+
+        let player = world.resource::<Player>();
+        BalanceReport {
+            level: self.level.name.clone(),
+            seed: self.seed,
+            cleared,
+            total_seconds,
+            gold: player.gold,
+            lives: player.lives,
+            towers,
+            waves,
+        }
+    }
+}
+
+impl BalanceReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("balance reports always serialize")
+    }
+
+    /// One `section,id,metric,value` row per number, so reports of two runs diff line by line.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("section,id,metric,value\n");
+        let mut row = |section: &str, id: &str, metric: &str, value: &dyn std::fmt::Display| {
+            writeln!(csv, "{},{},{},{}", section, id, metric, value).unwrap();
+        };
+        row("game", "", "level", &self.level);
+        row("game", "", "seed", &self.seed);
+        row("game", "", "cleared", &self.cleared);
+        row("game", "", "total_seconds", &format!("{:.3}", self.total_seconds));
+        row("game", "", "gold", &self.gold);
+        row("game", "", "lives", &self.lives);
+        for tower in &self.towers {
+            let id = tower.slot.to_string();
+            row("tower", &id, "tower_type", &format!("{:?}", tower.tower_type));
+            row("tower", &id, "shots_fired", &tower.shots_fired);
+            row("tower", &id, "hits", &tower.hits);
+            row("tower", &id, "damage", &tower.damage);
+            row("tower", &id, "dps", &format!("{:.3}", tower.dps));
+        }
+        for wave in &self.waves {
+            let id = wave.wave.to_string();
+            row("wave", &id, "spawned", &wave.spawned);
+            row("wave", &id, "killed", &wave.killed);
+            row("wave", &id, "leaked", &wave.leaked);
+        }
+        csv
+    }
+}
@@ -0,0 +1,57 @@
+//! Plays a tower placement plan on a level without a window and prints the metrics.
+
+use std::path::PathBuf;
+
+use bevy_tower_defense::*;
+use clap::Parser;
+
+/// Builds the towers of a placement plan, as soon as there is gold for each, simulates the level
+/// as fast as possible and prints per-tower and per-wave metrics.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// Placement plan (RON) listing the towers to build in order
+    #[arg(long, value_name = "FILE")]
+    plan: PathBuf,
+    /// Level file (RON) to play instead of the built-in one
+    #[arg(long, value_name = "FILE")]
+    level: Option<PathBuf>,
+    /// easy, normal, hard or custom multipliers, e.g. custom:health=1.5,gold=0.8
+    #[arg(long, default_value = "normal")]
+    difficulty: Difficulty,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Gold to start with instead of the difficulty's starting gold
+    #[arg(long)]
+    gold: Option<u32>,
+    /// Simulated seconds after which an uncleared level is given up on
+    #[arg(long, default_value_t = 600.0)]
+    max_seconds: f64,
+    #[arg(long, value_enum, default_value = "json")]
+    format: Format,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    log_to_stderr();
+    let plan = load_or_exit("plan", &cli.plan, PlacementPlan::load);
+    let level = match &cli.level {
+        Some(path) => load_or_exit("level", path, Level::load),
+        None => Level::default(),
+    };
+
+    let report = BalanceRun {
+        level,
+        plan,
+        difficulty: cli.difficulty,
+        seed: cli.seed,
+        max_seconds: cli.max_seconds,
+        starting_gold: cli.gold,
+    }
+    .simulate();
+
+    match cli.format {
+        Format::Json => println!("{}", report.to_json()),
+        Format::Csv => print!("{}", report.to_csv()),
+    }
+}
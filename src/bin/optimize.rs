@@ -0,0 +1,66 @@
+//! Searches for the strongest tower layouts of a level under a gold budget.
+
+use std::path::PathBuf;
+
+use bevy_tower_defense::*;
+use clap::Parser;
+
+/// Searches tower layouts that fit a gold budget by random-restart hill climbing, playing each
+/// one headlessly, and prints the best layouts found.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// Level file (RON) to search instead of the built-in one
+    #[arg(long, value_name = "FILE")]
+    level: Option<PathBuf>,
+    /// Gold to spend on the layout [default: the difficulty's starting gold]
+    #[arg(long)]
+    budget: Option<u32>,
+    /// easy, normal, hard or custom multipliers, e.g. custom:health=1.5,gold=0.8
+    #[arg(long, default_value = "normal")]
+    difficulty: Difficulty,
+    /// Seeds both the search and the simulated games
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    #[arg(long, default_value_t = 4)]
+    restarts: u32,
+    /// Neighbours tried in a row without improvement before restarting
+    #[arg(long, default_value_t = 10)]
+    patience: u32,
+    /// Simulated seconds after which an uncleared level is given up on
+    #[arg(long, default_value_t = 600.0)]
+    max_seconds: f64,
+    /// Number of layouts to report
+    #[arg(long, default_value_t = 5)]
+    top: usize,
+    #[arg(long, value_enum, default_value = "json")]
+    format: Format,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    log_to_stderr();
+    let level = match &cli.level {
+        Some(path) => load_or_exit("level", path, Level::load),
+        None => Level::default(),
+    };
+    let slots = level.slots.len();
+
+    let optimizer = Optimizer {
+        level,
+        difficulty: cli.difficulty,
+        seed: cli.seed,
+        budget: cli
+            .budget
+            .unwrap_or_else(|| cli.difficulty.modifiers().gold(STARTING_GOLD)),
+        restarts: cli.restarts,
+        patience: cli.patience,
+        max_seconds: cli.max_seconds,
+    };
+    let report = optimizer.run(cli.top);
+
+    match cli.format {
+        Format::Json => println!("{}", report.to_json()),
+        Format::Csv => print!("{}", report.to_csv(slots)),
+    }
+}
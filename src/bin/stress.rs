@@ -0,0 +1,34 @@
+//! Runs the built-in stress scenario without a window and prints frame times and entity counts.
+
+use bevy_tower_defense::*;
+use clap::Parser;
+
+/// Lines a long path with towers of every type, sends thousands of targets down it and reports
+/// the average and worst frame time, the peak entity counts and the time each system takes.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// Targets in the wave
+    #[arg(long, default_value_t = 2000)]
+    targets: u32,
+    /// Length of the path; there is a build slot on both sides every 4 units
+    #[arg(long, default_value_t = 200.0)]
+    length: f32,
+    /// Simulated seconds to run for, one tick per frame
+    #[arg(long, default_value_t = 60.0)]
+    seconds: f64,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let report = StressTest {
+        targets: cli.targets,
+        path_length: cli.length,
+        seconds: cli.seconds,
+        seed: cli.seed,
+    }
+    .run();
+    println!("{}", report.to_json());
+}
@@ -1,10 +1,17 @@
-use crate::{*, tower::tower::Tower};
+use crate::*;
 
 #[derive(Reflect, Component, Default)]
 #[reflect(Component)]
 pub struct Bullet {
     pub direction: Vec3,
     pub speed: f32,
+    /// How far the bullet moved on its last step. Collision sweeps over this distance, so fast
+    /// bullets can't skip past targets.
+    pub travelled: Vec3,
+    /// Tower that fired the bullet, credited with its hits. The bullet flies on if the tower
+    /// is gone.
+    #[reflect(ignore)]
+    pub source: Option<Entity>,
 }
 
 #[derive(Reflect, Component, Default)]
@@ -19,9 +26,11 @@ impl Plugin for BulletPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Bullet>()
             .register_type::<Lifetime>()
-            .add_system(bullet_collision)
-            .add_system(move_bullets)
-            .add_system(bullet_despawn);            
+            .init_resource::<ProjectilePool>()
+            .add_simulation_system(SimulationSet::Movement, move_bullets)
+            .add_simulation_system(SimulationSet::Collision, index_targets)
+            .add_simulation_system(SimulationSet::Collision, bullet_collision)
+            .add_simulation_system(SimulationSet::Cleanup, bullet_despawn);
     }
 
     fn name(&self) -> &str {
@@ -31,35 +40,41 @@ impl Plugin for BulletPlugin {
 
 fn bullet_despawn(
     mut commands: Commands,
-    mut bullets: Query<(Entity, &mut Lifetime)>,
-    time: Res<Time>,
+    mut pool: ResMut<ProjectilePool>,
+    mut bullets: Query<(Entity, &mut Lifetime, &PooledBullet)>,
 ) {
-    for (entity, mut lifetime) in &mut bullets {
-        lifetime.timer.tick(time.delta());
+    for (entity, mut lifetime, kind) in &mut bullets {
+        lifetime.timer.tick(SimulationClock::delta());
         if lifetime.timer.just_finished() {
-            commands.entity(entity).despawn_recursive();
+            pool.release(&mut commands, entity, *kind);
         }
     }
 }
 
-fn move_bullets(mut bullets: Query<(&Bullet, &mut Transform)>, time: Res<Time>) {
-    for (bullet, mut transform) in &mut bullets {
-        transform.translation += bullet.direction.normalize() * bullet.speed * time.delta_seconds();
+pub fn move_bullets(mut bullets: Query<(&mut Bullet, &mut Transform)>) {
+    for (mut bullet, mut transform) in &mut bullets {
+        bullet.travelled = bullet.direction.normalize() * bullet.speed * SimulationClock::delta_seconds();
+        transform.translation += bullet.travelled;
     }
 }
 
-fn bullet_collision(
+pub fn bullet_collision(
     mut commands: Commands,
-    bullets: Query<(Entity, &GlobalTransform), With<Bullet>>,
-    mut targets: Query<(&mut Health, &Transform), With<Target>>,
+    mut pool: ResMut<ProjectilePool>,
+    bullets: Query<(Entity, &Bullet, &PooledBullet, &Transform)>,
+    grid: Res<TargetGrid>,
+    mut hits: EventWriter<DamageEvent>,
 ) {
-    for (bullet, bullet_transform) in &bullets {
-        for (mut health, target_transform) in &mut targets {
-            if Vec3::distance(bullet_transform.translation(), target_transform.translation) < 0.4 {
-                commands.entity(bullet).despawn_recursive();
-                health.value -= 1;
-                break;
-            }
+    for (entity, bullet, kind, transform) in &bullets {
+        let from = transform.translation - bullet.travelled;
+        if let Some((target, _)) = grid.first_hit(from, transform.translation) {
+            pool.release(&mut commands, entity, *kind);
+            hits.send(DamageEvent {
+                source_tower: bullet.source,
+                target,
+                amount: BULLET_DAMAGE,
+                kind: DamageKind::Bullet(kind.0),
+            });
         }
     }
 }
@@ -1,7 +1,12 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, render::camera::Projection};
 
 use crate::*;
 
+/// Aspect ratio the scene was framed for. Narrower windows get a wider vertical
+/// field of view so the same horizontal slice of the level stays visible.
+const BASE_ASPECT: f32 = 16.0 / 9.0;
+const BASE_FOV: f32 = std::f32::consts::FRAC_PI_4;
+
 #[derive(Debug, Clone)]
 struct CameraMovement {
     forward: Vec3,
@@ -11,12 +16,12 @@ struct CameraMovement {
 
 }
 impl CameraMovement {
-    fn new(camera: Transform) -> CameraMovement {
+    fn new(camera: Transform, settings: &CameraSettings) -> CameraMovement {
         CameraMovement {
             forward: CameraMovement::create_forward(camera),
             left: CameraMovement::create_left(camera),
-            speed: 4.0,
-            rotate_speed: 0.4        
+            speed: settings.speed,
+            rotate_speed: settings.rotate_speed,
         }
     }
 
@@ -36,13 +41,14 @@ impl CameraMovement {
 }
 
 pub fn camera_controls(
-    keyboard: Res<Input<KeyCode>>,
+    input: ActionInput,
     mut camera_query: Query<&mut Transform, With<Camera3d>>,
     time: Res<Time>,
+    settings: Res<Settings>,
 ) {
     let mut camera = camera_query.single_mut();
-    let movement = CameraMovement::new(camera.clone());
-    camera_keyboard_control(&mut camera, movement, keyboard, time);
+    let movement = CameraMovement::new(*camera, &settings.camera);
+    camera_keyboard_control(&mut camera, movement, input, time);
 }
 
 // struct M2<'a, 'b> {
@@ -94,25 +100,56 @@ impl<'a, 'b> Movement<'a, 'b> {
     
 }
 
-fn camera_keyboard_control(camera: &mut Mut<Transform>, movement: CameraMovement, keyboard: Res<Input<KeyCode>>, time: Res<Time>) {
+fn camera_keyboard_control(camera: &mut Mut<Transform>, movement: CameraMovement, input: ActionInput, time: Res<Time>) {
     // let m2 = M2::new(&movement, &time);
     let mov: Movement = Movement::new(&movement, &time);
-    if keyboard.pressed(KeyCode::W) {
+    if input.pressed(Action::CameraForward) {
         camera.translation += mov.forward();
     }
-    if keyboard.pressed(KeyCode::S) {
+    if input.pressed(Action::CameraBack) {
         camera.translation -= mov.forward();
     }
-    if keyboard.pressed(KeyCode::A) {
+    if input.pressed(Action::CameraLeft) {
         camera.translation += mov.left();
     }
-    if keyboard.pressed(KeyCode::D) {
+    if input.pressed(Action::CameraRight) {
         camera.translation -= mov.left();
     }
-    if keyboard.pressed(KeyCode::Q) {
+    if input.pressed(Action::CameraRotateLeft) {
         camera.rotate_axis(Vec3::Y, mov.angle_rotate())
     }
-    if keyboard.pressed(KeyCode::E) {
+    if input.pressed(Action::CameraRotateRight) {
         camera.rotate_axis(Vec3::Y, -mov.angle_rotate())
     }    
 }
+
+fn fov_for_aspect(aspect: f32) -> f32 {
+    if aspect >= BASE_ASPECT {
+        BASE_FOV
+    } else {
+        2.0 * ((BASE_FOV / 2.0).tan() * BASE_ASPECT / aspect).atan()
+    }
+}
+
+pub fn camera_fit_window(
+    windows: Res<Windows>,
+    mut camera_query: Query<&mut Projection, With<Camera3d>>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) if window.height() > 0.0 => window,
+        _ => return,
+    };
+    let fov = fov_for_aspect(window.width() / window.height());
+    for mut projection in &mut camera_query {
+        let outdated = matches!(
+            projection.as_ref(),
+            Projection::Perspective(perspective) if perspective.fov != fov
+        );
+        if !outdated {
+            continue;
+        }
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = fov;
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+
+/// Output format of the command-line tools' reports.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+/// Prints `message` to stderr and exits with a failure status.
+pub fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+/// Reads the `what` file at `path` with `load`, e.g. `Level::load`, exiting if it cannot.
+pub fn load_or_exit<T>(what: &str, path: &PathBuf, load: impl FnOnce(&PathBuf) -> Result<T, String>) -> T {
+    load(path).unwrap_or_else(|err| fail(&format!("Could not load {} {}: {}", what, path.display(), err)))
+}
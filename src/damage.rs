@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Health a bullet takes off the target it hits.
+pub const BULLET_DAMAGE: i32 = 1;
+
+/// What dealt a `DamageEvent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageKind {
+    /// A bullet fired by a tower of this type.
+    Bullet(TowerType),
+}
+
+/// A hit on a target. Sent in the collision phase and taken off the target's `Health` in the
+/// damage phase.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DamageEvent {
+    /// Tower credited with the hit. It may have been despawned since it fired, so look it up
+    /// rather than assume it exists.
+    pub source_tower: Option<Entity>,
+    pub target: Entity,
+    pub amount: i32,
+    pub kind: DamageKind,
+}
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_simulation_system(SimulationSet::Damage, apply_damage);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn apply_damage(mut hits: EventReader<DamageEvent>, mut targets: Query<&mut Health, With<Target>>) {
+    for hit in hits.iter() {
+        if let Ok(mut health) = targets.get_mut(hit.target) {
+            health.value -= hit.amount;
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Multipliers applied to the base game values when a level starts.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DifficultyModifiers {
+    pub health: f32,
+    pub speed: f32,
+    pub bounty: f32,
+    pub gold: f32,
+    pub lives: f32,
+}
+
+impl Default for DifficultyModifiers {
+    fn default() -> Self {
+        DifficultyModifiers {
+            health: 1.0,
+            speed: 1.0,
+            bounty: 1.0,
+            gold: 1.0,
+            lives: 1.0,
+        }
+    }
+}
+
+impl DifficultyModifiers {
+    fn scale(value: f32, multiplier: f32) -> f32 {
+        value * multiplier.max(0.0)
+    }
+
+    pub fn health(&self, base: i32) -> i32 {
+        (Self::scale(base as f32, self.health).round() as i32).max(1)
+    }
+
+    pub fn speed(&self, base: f32) -> f32 {
+        Self::scale(base, self.speed)
+    }
+
+    pub fn bounty(&self, base: u32) -> u32 {
+        Self::scale(base as f32, self.bounty).round() as u32
+    }
+
+    pub fn gold(&self, base: u32) -> u32 {
+        Self::scale(base as f32, self.gold).round() as u32
+    }
+
+    pub fn lives(&self, base: u32) -> u32 {
+        (Self::scale(base as f32, self.lives).round() as u32).max(1)
+    }
+}
+
+/// Difficulty chosen before a level starts, read when enemies and the player are spawned.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Custom(DifficultyModifiers),
+}
+
+impl Difficulty {
+    pub fn modifiers(&self) -> DifficultyModifiers {
+        match self {
+            Difficulty::Easy => DifficultyModifiers {
+                health: 0.7,
+                speed: 0.8,
+                bounty: 1.25,
+                gold: 1.5,
+                lives: 2.0,
+            },
+            Difficulty::Normal => DifficultyModifiers::default(),
+            Difficulty::Hard => DifficultyModifiers {
+                health: 1.6,
+                speed: 1.25,
+                bounty: 0.8,
+                gold: 0.75,
+                lives: 0.5,
+            },
+            Difficulty::Custom(modifiers) => *modifiers,
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    /// Parses the preset names used on the command line, or `custom:` followed by
+    /// `name=multiplier` pairs, e.g. `custom:health=1.5,gold=0.8`. Multipliers left out stay at 1.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let lowercase = name.to_ascii_lowercase();
+        if let Some(multipliers) = lowercase.strip_prefix("custom:") {
+            return DifficultyModifiers::parse(multipliers).map(Difficulty::Custom);
+        }
+        match lowercase.as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!(
+                "unknown difficulty '{}' (expected easy, normal, hard or custom:health=1.5,...)",
+                name
+            )),
+        }
+    }
+}
+
+impl DifficultyModifiers {
+    fn parse(multipliers: &str) -> Result<Self, String> {
+        let mut modifiers = DifficultyModifiers::default();
+        for pair in multipliers.split(',').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected name=multiplier, found '{}'", pair))?;
+            let value = match value.trim().parse::<f32>() {
+                Ok(value) if value >= 0.0 && value.is_finite() => value,
+                _ => return Err(format!("'{}' is not a multiplier of 0 or more", value)),
+            };
+            let field = match name.trim() {
+                "health" => &mut modifiers.health,
+                "speed" => &mut modifiers.speed,
+                "bounty" => &mut modifiers.bounty,
+                "gold" => &mut modifiers.gold,
+                "lives" => &mut modifiers.lives,
+                other => {
+                    return Err(format!(
+                        "unknown multiplier '{}' (expected health, speed, bounty, gold or lives)",
+                        other
+                    ))
+                }
+            };
+            *field = value;
+        }
+        Ok(modifiers)
+    }
+}
@@ -0,0 +1,73 @@
+use std::io::IsTerminal;
+
+use bevy::{app::AppExit, app::ScheduleRunnerSettings, prelude::*};
+use tracing_subscriber::EnvFilter;
+
+use crate::*;
+
+/// Runs the gameplay plugins without a window, renderer or asset loading,
+/// using placeholder scene handles for everything that would be drawn.
+///
+/// The simulation advances one tick per update as fast as the CPU allows.
+/// Logging is left to the app, so tools can keep stdout for their own output.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ScheduleRunnerSettings::default())
+            .insert_resource(SimulationClock::new(ClockMode::Stepped(1)))
+            .add_plugins(MinimalPlugins)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .insert_resource(GameAssets::placeholder())
+            .add_plugins(GamePlugins);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Sends the log to stderr, for tools that print their report to stdout. Shows warnings and the
+/// game's own messages unless `RUST_LOG` says otherwise, but not the seed of every game played,
+/// which the reports include.
+pub fn log_to_stderr() {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("warn,bevy_tower_defense=info,bevy_tower_defense::rng=warn"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .init();
+}
+
+/// Spawns the build slots without any visuals.
+pub fn spawn_headless_scene(mut commands: Commands, level: Res<Level>) {
+    for (slot, position) in level.slots.iter().enumerate() {
+        spawn_build_slot(&mut commands, *position, slot);
+    }
+}
+
+/// Reports the outcome and exits once every wave has died or leaked.
+pub fn exit_when_cleared(
+    waves: Res<WaveState>,
+    player: Res<Player>,
+    clock: Res<SimulationClock>,
+    pool: Res<ProjectilePool>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if waves.is_cleared() {
+        info!(
+            "Level cleared after {:.1}s with {} gold and {} lives left",
+            clock.elapsed_seconds(),
+            player.gold,
+            player.lives
+        );
+        let metrics = pool.metrics();
+        info!(
+            "Bullet pool: {} bullets created, {} reuses",
+            metrics.created, metrics.reused
+        );
+        exit.send(AppExit);
+    }
+}
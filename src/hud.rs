@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+#[derive(Component)]
+pub struct SeedLabel;
+
+/// On-screen information that is not part of the build menu.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_seed_label)
+            .add_system(update_seed_label);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn spawn_seed_label(mut commands: Commands, asset_server: Res<AssetServer>, rng: Res<GameRng>) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 16.0,
+        color: Color::rgba(1.0, 1.0, 1.0, 0.7),
+    };
+    commands
+        .spawn_bundle(
+            TextBundle::from_sections([
+                TextSection::new("seed-label", style.clone()),
+                TextSection::new(format!(": {}", rng.seed()), style),
+            ])
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(8.0),
+                    bottom: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(Localized::new("seed-label"))
+        .insert(SeedLabel);
+}
+
+/// Shows the seed of a loaded game, which replaces the `GameRng`.
+pub fn update_seed_label(rng: Res<GameRng>, mut labels: Query<&mut Text, With<SeedLabel>>) {
+    if !rng.is_changed() {
+        return;
+    }
+    let seed = format!(": {}", rng.seed());
+    for mut text in &mut labels {
+        if text.sections[1].value != seed {
+            text.sections[1].value = seed.clone();
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Everything the player can trigger from the keyboard or mouse.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    CameraForward,
+    CameraBack,
+    CameraLeft,
+    CameraRight,
+    CameraRotateLeft,
+    CameraRotateRight,
+    BuildTomato,
+    BuildPotato,
+    BuildCabbage,
+    QuickSave,
+    QuickLoad,
+    TogglePerformance,
+    CycleLocale,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::CameraForward,
+        Action::CameraBack,
+        Action::CameraLeft,
+        Action::CameraRight,
+        Action::CameraRotateLeft,
+        Action::CameraRotateRight,
+        Action::BuildTomato,
+        Action::BuildPotato,
+        Action::BuildCabbage,
+        Action::QuickSave,
+        Action::QuickLoad,
+        Action::TogglePerformance,
+        Action::CycleLocale,
+    ];
+
+    fn default_bindings(&self) -> Vec<Binding> {
+        let key = match self {
+            Action::CameraForward => KeyCode::W,
+            Action::CameraBack => KeyCode::S,
+            Action::CameraLeft => KeyCode::A,
+            Action::CameraRight => KeyCode::D,
+            Action::CameraRotateLeft => KeyCode::Q,
+            Action::CameraRotateRight => KeyCode::E,
+            Action::BuildTomato => KeyCode::Key1,
+            Action::BuildPotato => KeyCode::Key2,
+            Action::BuildCabbage => KeyCode::Key3,
+            Action::QuickSave => KeyCode::F5,
+            Action::QuickLoad => KeyCode::F9,
+            Action::TogglePerformance => KeyCode::F3,
+            Action::CycleLocale => KeyCode::F2,
+        };
+        vec![Binding::Key(key)]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+/// Maps each action to the keys and mouse buttons that trigger it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct InputBindings(BTreeMap<Action, Vec<Binding>>);
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        InputBindings(
+            Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_bindings()))
+                .collect(),
+        )
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Adds `binding` to `action`, refusing if another action already uses it.
+    pub fn bind(&mut self, action: Action, binding: Binding) -> Result<(), Action> {
+        if let Some(other) = self.action_for(binding).filter(|other| *other != action) {
+            return Err(other);
+        }
+        let bindings = self.0.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, action: Action, binding: Binding) {
+        if let Some(bindings) = self.0.get_mut(&action) {
+            bindings.retain(|bound| *bound != binding);
+        }
+    }
+
+    pub fn action_for(&self, binding: Binding) -> Option<Action> {
+        self.0
+            .iter()
+            .find(|(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| *action)
+    }
+
+    /// Every binding used by more than one action, with the actions that share it.
+    pub fn conflicts(&self) -> Vec<(Binding, Vec<Action>)> {
+        let mut conflicts: Vec<(Binding, Vec<Action>)> = Vec::new();
+        for (action, bindings) in &self.0 {
+            for binding in bindings {
+                match conflicts.iter_mut().find(|(bound, _)| bound == binding) {
+                    Some((_, actions)) => actions.push(*action),
+                    None => conflicts.push((*binding, vec![*action])),
+                }
+            }
+        }
+        conflicts.retain(|(_, actions)| actions.len() > 1);
+        conflicts
+    }
+
+    /// Fills in defaults for actions missing from an older settings file
+    /// and drops duplicate bindings within an action. Conflicts between actions are kept, and
+    /// reported once the game starts.
+    pub fn validated(mut self) -> Self {
+        for action in Action::ALL {
+            self.0
+                .entry(action)
+                .or_insert_with(|| action.default_bindings());
+        }
+        for bindings in self.0.values_mut() {
+            let mut unique = Vec::new();
+            for binding in bindings.drain(..) {
+                if !unique.contains(&binding) {
+                    unique.push(binding);
+                }
+            }
+            *bindings = unique;
+        }
+        self
+    }
+}
+
+/// Reads actions through the bindings in the user settings.
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    settings: Res<'w, Settings>,
+    keyboard: Res<'w, Input<KeyCode>>,
+    mouse: Res<'w, Input<MouseButton>>,
+    #[system_param(ignore)]
+    marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl<'w, 's> ActionInput<'w, 's> {
+    fn any(
+        &self,
+        action: Action,
+        key: fn(&Input<KeyCode>, KeyCode) -> bool,
+        mouse: fn(&Input<MouseButton>, MouseButton) -> bool,
+    ) -> bool {
+        self.settings
+            .bindings
+            .get(action)
+            .iter()
+            .any(|binding| match binding {
+                Binding::Key(code) => key(&self.keyboard, *code),
+                Binding::Mouse(button) => mouse(&self.mouse, *button),
+            })
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.any(action, Input::pressed, Input::pressed)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.any(action, Input::just_pressed, Input::just_pressed)
+    }
+}
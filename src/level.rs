@@ -0,0 +1,164 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// A group of identical targets lined up behind the start of the path.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Wave {
+    pub count: u32,
+    /// Distance between two targets of the wave.
+    pub spacing: f32,
+    /// Seconds to wait after the previous wave is cleared.
+    #[serde(default)]
+    pub delay: f32,
+    pub health: i32,
+    pub speed: f32,
+    pub bounty: u32,
+    /// Collider radius of the targets.
+    #[serde(default = "default_target_radius")]
+    pub radius: f32,
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Wave {
+            count: 24,
+            spacing: 4.0,
+            delay: 0.0,
+            health: TARGET_HEALTH,
+            speed: TARGET_SPEED,
+            bounty: TARGET_BOUNTY,
+            radius: TARGET_RADIUS,
+        }
+    }
+}
+
+/// Layout of a map: where towers can be built, where targets walk and in which waves.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Level {
+    pub name: String,
+    /// Positions of the `Tower_Base` slots the player can build on.
+    pub slots: Vec<Vec3>,
+    /// Where the first target of each wave spawns; the others line up behind it.
+    pub path_start: Vec3,
+    /// Targets that walk past this x coordinate escape and cost the player a life.
+    pub path_end: f32,
+    pub waves: Vec<Wave>,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        let mut slots = Vec::new();
+        for i in 0..10 {
+            for j in 0..2 {
+                slots.push(Vec3::new(4.0 * i as f32 + j as f32, 0.8, 8.0 * j as f32));
+            }
+        }
+        Level {
+            name: "meadow".to_string(),
+            slots,
+            path_start: Vec3::new(-4.0, 0.4, 2.5),
+            path_end: 45.0,
+            waves: vec![Wave::default()],
+        }
+    }
+}
+
+impl Level {
+    pub fn load(path: &PathBuf) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        ron::from_str(&source).map_err(|err| err.to_string())
+    }
+}
+
+/// Progress through the waves of the current level.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct WaveState {
+    next: usize,
+    waited: f32,
+    cleared: bool,
+}
+
+impl WaveState {
+    /// Index of the wave that spawns next.
+    pub fn next_wave(&self) -> usize {
+        self.next
+    }
+
+    /// Whether every wave has spawned and died or leaked.
+    pub fn is_cleared(&self) -> bool {
+        self.cleared
+    }
+}
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Level>()
+            .init_resource::<WaveState>()
+            .add_simulation_system(SimulationSet::Spawning, spawn_waves);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Spawns the next wave once the previous one is gone and its delay has passed.
+fn spawn_waves(
+    mut commands: Commands,
+    level: Res<Level>,
+    mut waves: ResMut<WaveState>,
+    mut stats: ResMut<GameStats>,
+    targets: Query<(), With<Target>>,
+    assets: Res<GameAssets>,
+    difficulty: Res<Difficulty>,
+) {
+    if waves.is_cleared() || !targets.is_empty() {
+        return;
+    }
+    let index = waves.next;
+    let wave = match level.waves.get(index) {
+        Some(wave) => wave,
+        None => {
+            waves.cleared = true;
+            return;
+        }
+    };
+    if waves.waited < wave.delay {
+        waves.waited += SimulationClock::delta_seconds();
+        return;
+    }
+    spawn_wave(&mut commands, &assets, &level, index, &difficulty);
+    stats.wave_mut(index).spawned += wave.count;
+    waves.next += 1;
+    waves.waited = 0.0;
+}
+
+pub fn spawn_wave(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    level: &Level,
+    index: usize,
+    difficulty: &Difficulty,
+) {
+    let wave = &level.waves[index];
+    let modifiers = difficulty.modifiers();
+    let target = Target {
+        speed: modifiers.speed(wave.speed),
+        bounty: modifiers.bounty(wave.bounty),
+        wave: index,
+        radius: wave.radius,
+    };
+    let health = Health {
+        value: modifiers.health(wave.health),
+    };
+    for i in 0..wave.count {
+        let position = level.path_start - Vec3::X * wave.spacing * i as f32;
+        spawn_target(commands, assets, position, target, health);
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::{app::PluginGroupBuilder, prelude::*, utils::FloatOrd};
+use bevy_inspector_egui::Inspectable;
+use bevy_mod_picking::*;
+
+pub struct GameAssets {
+    pub tower_base_scene: Handle<Scene>,
+    pub tomato_tower_scene: Handle<Scene>,
+    pub tomato_scene: Handle<Scene>,
+    pub potato_tower_scene: Handle<Scene>,
+    pub potato_scene: Handle<Scene>,
+    pub cabbage_tower_scene: Handle<Scene>,
+    pub cabbage_scene: Handle<Scene>,
+    pub target_scene: Handle<Scene>,
+}
+
+impl GameAssets {
+    pub fn load(assets: &AssetServer) -> Self {
+        GameAssets {
+            tower_base_scene: assets.load("TowerBase.glb#Scene0"),
+            tomato_tower_scene: assets.load("TomatoTower.glb#Scene0"),
+            tomato_scene: assets.load("Tomato.glb#Scene0"),
+            potato_tower_scene: assets.load("PotatoTower.glb#Scene0"),
+            potato_scene: assets.load("Potato.glb#Scene0"),
+            cabbage_tower_scene: assets.load("CabbageTower.glb#Scene0"),
+            cabbage_scene: assets.load("Cabbage.glb#Scene0"),
+            target_scene: assets.load("Target.glb#Scene0"),
+        }
+    }
+
+    /// Empty handles for running the game without an asset server.
+    pub fn placeholder() -> Self {
+        GameAssets {
+            tower_base_scene: Handle::default(),
+            tomato_tower_scene: Handle::default(),
+            tomato_scene: Handle::default(),
+            potato_tower_scene: Handle::default(),
+            potato_scene: Handle::default(),
+            cabbage_tower_scene: Handle::default(),
+            cabbage_scene: Handle::default(),
+            target_scene: Handle::default(),
+        }
+    }
+}
+
+mod bullet;
+mod target;
+pub mod tower;
+mod camera;
+mod difficulty;
+mod player;
+mod localization;
+mod settings;
+mod input;
+mod level;
+mod headless;
+mod simulation;
+mod rng;
+mod hud;
+mod actions;
+mod replay;
+mod stats;
+mod balance;
+mod autoplayer;
+mod optimizer;
+mod save;
+mod spatial;
+mod pool;
+mod damage;
+mod performance;
+mod stress;
+mod cli;
+
+pub use tower::*;
+pub use bullet::*;
+pub use target::*;
+pub use camera::*;
+pub use difficulty::*;
+pub use player::*;
+pub use localization::*;
+pub use settings::*;
+pub use input::*;
+pub use level::*;
+pub use headless::*;
+pub use simulation::*;
+pub use rng::*;
+pub use hud::*;
+pub use actions::*;
+pub use replay::*;
+pub use stats::*;
+pub use balance::*;
+pub use autoplayer::*;
+pub use optimizer::*;
+pub use save::*;
+pub use spatial::*;
+pub use pool::*;
+pub use damage::*;
+pub use performance::*;
+pub use stress::*;
+pub use cli::*;
+pub use tower::{shooter::*, tower::*, tower_type::*, ui::*};
+
+/// The gameplay plugins shared by the windowed game and headless runs.
+pub struct GamePlugins;
+
+impl PluginGroup for GamePlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group.add(SimulationPlugin);
+        group.add(GameRngPlugin);
+        group.add(StatsPlugin);
+        group.add(AutoPlayerPlugin);
+        group.add(ActionsPlugin);
+        group.add(LevelPlugin);
+        group.add(SavePlugin);
+        group.add(SpatialPlugin);
+        group.add(PlayerPlugin);
+        group.add(TowerPlugin);
+        group.add(TargetPlugin);
+        group.add(BulletPlugin);
+        group.add(DamagePlugin);
+        group.add(PerformancePlugin);
+    }
+}
@@ -0,0 +1,217 @@
+use bevy::{
+    asset::{AssetLoader, Error, LoadContext, LoadedAsset},
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+
+use crate::*;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Locales with a string table in `assets/locales`, in the order `Action::CycleLocale` goes through them.
+pub const LOCALES: [&str; 4] = ["en", "de", "es", "fr"];
+
+/// Key/value strings for one locale, loaded from `assets/locales/<locale>.lang`.
+///
+/// Each line is `key = value`; blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Default, TypeUuid)]
+#[uuid = "5a3c3b8e-2f43-4a8e-9d0f-6f8f2b1d7c41"]
+pub struct StringTable {
+    strings: HashMap<String, String>,
+}
+
+impl StringTable {
+    pub fn parse(source: &str) -> Self {
+        let strings = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        StringTable { strings }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(String::as_str)
+    }
+}
+
+#[derive(Default)]
+pub struct StringTableLoader;
+
+impl AssetLoader for StringTableLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let table = StringTable::parse(std::str::from_utf8(bytes)?);
+            load_context.set_default_asset(LoadedAsset::new(table));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lang"]
+    }
+}
+
+/// The active language, e.g. `"en"` or `"fr"`. Changing it at runtime reloads all localized text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale(pub String);
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale(DEFAULT_LOCALE.to_string())
+    }
+}
+
+/// Handles to the string tables of the active locale and the fallback locale.
+#[derive(Default)]
+pub struct Localization {
+    active: Handle<StringTable>,
+    fallback: Handle<StringTable>,
+}
+
+/// Marks an entity whose `Name` and first `Text` section are looked up from the string tables by `key`.
+#[derive(Component, Debug, Clone)]
+pub struct Localized {
+    pub key: String,
+}
+
+impl Localized {
+    pub fn new(key: impl Into<String>) -> Self {
+        Localized { key: key.into() }
+    }
+}
+
+#[derive(SystemParam)]
+pub struct Localizer<'w, 's> {
+    localization: Res<'w, Localization>,
+    tables: Res<'w, Assets<StringTable>>,
+    #[system_param(ignore)]
+    marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl<'w, 's> Localizer<'w, 's> {
+    /// Looks up `key` in the active locale, then the fallback locale, and finally returns the key itself.
+    pub fn get(&self, key: &str) -> String {
+        [&self.localization.active, &self.localization.fallback]
+            .into_iter()
+            .filter_map(|handle| self.tables.get(handle))
+            .find_map(|table| table.get(key))
+            .unwrap_or(key)
+            .to_string()
+    }
+
+    /// Whether the strings to show changed since the last call, because the locale changed or
+    /// its string tables were (re)loaded.
+    pub fn changed(&self, events: &mut EventReader<AssetEvent<StringTable>>) -> bool {
+        strings_changed(&self.localization, events)
+    }
+}
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<StringTable>()
+            .init_asset_loader::<StringTableLoader>()
+            .init_resource::<Locale>()
+            .init_resource::<Localization>()
+            .add_system(cycle_locale)
+            .add_system(load_locale.after(cycle_locale))
+            .add_system(localize_window_title.after(load_locale))
+            .add_system(localize_entities.after(load_locale));
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn locale_path(locale: &str) -> String {
+    format!("locales/{}.lang", locale)
+}
+
+/// Switches to the next of the `LOCALES`, or back to the first from one not in the list.
+pub fn cycle_locale(input: ActionInput, mut locale: ResMut<Locale>) {
+    if input.just_pressed(Action::CycleLocale) {
+        let next = LOCALES
+            .iter()
+            .position(|known| *known == locale.0)
+            .map_or(0, |index| (index + 1) % LOCALES.len());
+        locale.0 = LOCALES[next].to_string();
+    }
+}
+
+fn load_locale(
+    locale: Res<Locale>,
+    mut localization: ResMut<Localization>,
+    asset_server: Res<AssetServer>,
+) {
+    if locale.is_changed() {
+        localization.active = asset_server.load(&locale_path(&locale.0));
+        localization.fallback = asset_server.load(&locale_path(DEFAULT_LOCALE));
+    }
+}
+
+fn strings_changed(
+    localization: &Res<Localization>,
+    events: &mut EventReader<AssetEvent<StringTable>>,
+) -> bool {
+    let mut changed = localization.is_changed();
+    for event in events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            changed |= *handle == localization.active || *handle == localization.fallback;
+        }
+    }
+    changed
+}
+
+fn localize_window_title(
+    localizer: Localizer,
+    mut events: EventReader<AssetEvent<StringTable>>,
+    mut windows: ResMut<Windows>,
+) {
+    if strings_changed(&localizer.localization, &mut events) {
+        if let Some(window) = windows.get_primary_mut() {
+            window.set_title(localizer.get("window-title"));
+        }
+    }
+}
+
+type LocalizedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Localized,
+        ChangeTrackers<Localized>,
+        Option<&'static mut Name>,
+        Option<&'static mut Text>,
+    ),
+>;
+
+fn localize_entities(
+    localizer: Localizer,
+    mut events: EventReader<AssetEvent<StringTable>>,
+    mut entities: LocalizedQuery,
+) {
+    let changed = strings_changed(&localizer.localization, &mut events);
+    for (localized, tracker, name, mut text) in &mut entities {
+        if !changed && !tracker.is_changed() {
+            continue;
+        }
+        let value = localizer.get(&localized.key);
+        if let Some(mut name) = name {
+            name.set(value.clone());
+        }
+        if let Some(section) = text.as_mut().and_then(|text| text.sections.first_mut()) {
+            section.value = value;
+        }
+    }
+}
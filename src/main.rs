@@ -1,73 +1,192 @@
-use bevy::{pbr::NotShadowCaster, prelude::*, utils::FloatOrd};
-use bevy_inspector_egui::{Inspectable, RegisterInspectable, WorldInspectorPlugin};
+use std::path::PathBuf;
+
+use bevy::{log::LogPlugin, pbr::NotShadowCaster, prelude::*};
+use bevy_inspector_egui::{RegisterInspectable, WorldInspectorPlugin};
 use bevy_mod_picking::*;
+use bevy_tower_defense::*;
+use clap::Parser;
 
-pub const HEIGHT: f32 = 720.0;
-pub const WIDTH: f32 = 1280.0;
+/// A tower defense game. Without options, plays the built-in level in a window.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// Level file (RON) to play instead of the built-in one
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    level: Option<PathBuf>,
+    /// Seed for all gameplay randomness [default: random]
+    #[arg(long, conflicts_with = "replay")]
+    seed: Option<u64>,
+    /// easy, normal, hard or custom multipliers, e.g. custom:health=1.5,gold=0.8
+    #[arg(long, default_value = "normal", conflicts_with = "replay")]
+    difficulty: Difficulty,
+    /// Simulation speed multiplier; headless runs always go as fast as possible
+    #[arg(long, default_value_t = 1.0, value_parser = parse_speed)]
+    speed: f64,
+    /// Run without a window and exit once the level is cleared
+    #[arg(long)]
+    headless: bool,
+    /// Play back a recorded replay, with its level, seed and difficulty
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+    /// Record the replay to this file [default: last-replay.ron next to the settings]
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["replay", "load"])]
+    record: Option<PathBuf>,
+    /// Continue a saved game; it is not recorded, as a replay always starts with the level
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    load: Option<PathBuf>,
+    /// Let a bot play; headless runs use greedy unless a replay is given
+    #[arg(long, value_name = "STRATEGY", value_parser = ["greedy", "random"])]
+    autoplay: Option<String>,
+    /// Do not show the world inspector
+    #[arg(long)]
+    no_inspector: bool,
+    /// Log frame times, entity counts and system timings every second
+    #[arg(long)]
+    perf_log: bool,
+    /// Append the same performance figures to this CSV file every second
+    #[arg(long, value_name = "FILE")]
+    perf_csv: Option<PathBuf>,
+}
 
-pub struct GameAssets {
-    bullet_scene: Handle<Scene>,
-    tower_base_scene: Handle<Scene>,
-    tomato_tower_scene: Handle<Scene>,
-    tomato_scene: Handle<Scene>,
-    potato_tower_scene: Handle<Scene>,
-    potato_scene: Handle<Scene>,
-    cabbage_tower_scene: Handle<Scene>,
-    cabbage_scene: Handle<Scene>,
-    target_scene: Handle<Scene>,
+fn parse_speed(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!("'{}' is not a positive number", value)),
+    }
 }
 
-mod bullet;
-mod target;
-mod tower;
-mod camera;
+fn main() {
+    let cli = Cli::parse();
+    let replay = cli.replay.as_ref().map(|path| load_or_exit("replay", path, Replay::load));
+    let level = match &cli.level {
+        Some(path) => load_or_exit("level", path, Level::load),
+        None => Level::default(),
+    };
 
-pub use tower::*;
-pub use bullet::*;
-pub use target::*;
-pub use camera::*;
-use tower::{tower::TowerPlugin, tower_type::TowerType};
+    let performance_log = PerformanceLog::new(cli.perf_log, cli.perf_csv.as_deref()).unwrap_or_else(|err| {
+        let path = cli.perf_csv.as_ref().unwrap();
+        fail(&format!("Could not open {}: {}", path.display(), err))
+    });
 
-fn main() {
-    App::new()
+    let mut app = App::new();
+    app.insert_resource(performance_log);
+    if cli.headless {
+        if replay.is_none() {
+            let strategy = cli.autoplay.as_deref().unwrap_or("greedy");
+            app.insert_resource(AutoPlayer::from_name(strategy).unwrap());
+        }
+        insert_game_setup(&mut app, &cli, replay, level, None);
+        app.add_plugin(LogPlugin)
+            .add_plugin(HeadlessPlugin)
+            .add_startup_system(spawn_headless_scene)
+            .add_system(exit_when_cleared)
+            .run();
+        return;
+    }
+
+    let (settings, settings_error) = match Settings::load() {
+        Ok(settings) => (settings, None),
+        Err(err) => (Settings::default(), Some(err)),
+    };
+    if let Some(strategy) = &cli.autoplay {
+        app.insert_resource(AutoPlayer::from_name(strategy).unwrap());
+    }
+    insert_game_setup(&mut app, &cli, replay, level, ReplayRecorder::default_path());
+    let mut clock = SimulationClock::default();
+    clock.speed = cli.speed;
+    app
         // Window Setup
         .insert_resource(ClearColor(Color::rgb(0.2, 0.2, 0.2)))
-        .insert_resource(WindowDescriptor {
-            width: WIDTH,
-            height: HEIGHT,
-            title: "Bevy Tower Defense".to_string(),
-            resizable: false,
-            ..Default::default()
-        })
-        .add_plugins(DefaultPlugins)
+        .insert_resource(settings.window_descriptor())
+        .insert_resource(Locale(settings.locale.clone()))
+        .insert_resource(settings)
+        .insert_resource(clock)
+        .add_plugins(DefaultPlugins);
+    if !cli.no_inspector {
         // Inspector Setup
-        .add_plugin(WorldInspectorPlugin::new())
+        app.add_plugin(WorldInspectorPlugin::new())
+            .register_inspectable::<TowerType>();
+    }
+    app
         // Mod Picking
         .add_plugins(DefaultPickingPlugins)
         // Our Systems
-        .add_plugin(TowerPlugin)
-        .add_plugin(TargetPlugin)
-        .add_plugin(BulletPlugin)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(LocalizationPlugin)
+        .add_plugins(GamePlugins)
+        .add_plugin(TowerUiPlugin)
+        .add_plugin(HudPlugin)
+        .add_plugin(PerformanceOverlayPlugin)
+        // Logging only starts with DefaultPlugins.
+        .add_startup_system(move || {
+            if let Some(err) = &settings_error {
+                warn!("{}", err);
+            }
+        })
         .add_startup_system(spawn_basic_scene)
         .add_startup_system(spawn_camera)
-        .register_inspectable::<TowerType>()
         .add_startup_system_to_stage(StartupStage::PreStartup, asset_loading)
         .add_system(camera_controls)
-        .run();
+        .add_system(camera_fit_window)
+        .add_system(decorate_build_slots)
+        .add_system(quick_save_keys)
+        .add_system_to_stage(
+            SimulationStage,
+            autosave_between_waves.exclusive_system().at_end().after(TickEnd),
+        );
+    if let Some(path) = SavedGame::autosave_path() {
+        app.insert_resource(Autosave::new(path));
+    }
+    app.run();
+}
+
+/// Takes the level, seed and difficulty from the replay being played back, or from the options
+/// while recording a new replay to `--record` (falling back to `default_record_path`).
+/// A game continued from `--load` is not recorded, since replays start with the level. Its file is
+/// read here, exiting if it cannot be, and queued to be restored once the scene is set up.
+fn insert_game_setup(
+    app: &mut App,
+    cli: &Cli,
+    replay: Option<Replay>,
+    level: Level,
+    default_record_path: Option<PathBuf>,
+) {
+    match replay {
+        Some(replay) => {
+            app.insert_resource(GameRng::new(replay.seed))
+                .insert_resource(replay.difficulty)
+                .insert_resource(ReplayPlayback::new(&replay))
+                .insert_resource(replay.level);
+        }
+        None => {
+            let rng = match cli.seed {
+                Some(seed) => GameRng::new(seed),
+                None => GameRng::default(),
+            };
+            let record_path = cli.record.clone().or(default_record_path);
+            if let Some(path) = record_path.filter(|_| cli.load.is_none()) {
+                app.insert_resource(ReplayRecorder::new(
+                    path,
+                    rng.seed(),
+                    cli.difficulty,
+                    level.clone(),
+                ));
+            }
+            app.insert_resource(rng)
+                .insert_resource(cli.difficulty)
+                .insert_resource(level);
+        }
+    }
+    if let Some(path) = &cli.load {
+        let mut requests = SaveRequests::default();
+        let game = load_or_exit("saved game", path, SavedGame::load);
+        requests.push(SaveRequest::Restore(Box::new(game)));
+        app.insert_resource(requests);
+    }
 }
 
 fn asset_loading(mut commands: Commands, assets: Res<AssetServer>) {
-    commands.insert_resource(GameAssets {
-        bullet_scene: assets.load("Tomato.glb#Scene0"),
-        tower_base_scene: assets.load("TowerBase.glb#Scene0"),
-        tomato_tower_scene: assets.load("TomatoTower.glb#Scene0"),
-        tomato_scene: assets.load("Tomato.glb#Scene0"),
-        potato_tower_scene: assets.load("PotatoTower.glb#Scene0"),
-        potato_scene: assets.load("Potato.glb#Scene0"),
-        cabbage_tower_scene: assets.load("CabbageTower.glb#Scene0"),
-        cabbage_scene: assets.load("Cabbage.glb#Scene0"),
-        target_scene: assets.load("Target.glb#Scene0"),
-    });
+    commands.insert_resource(GameAssets::load(&assets));
 }
 
 /* Selection testing system
@@ -84,7 +203,7 @@ fn spawn_basic_scene(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    game_assets: Res<GameAssets>,
+    level: Res<Level>,
 ) {
     commands
         .spawn_bundle(PbrBundle {
@@ -94,48 +213,8 @@ fn spawn_basic_scene(
         })
         .insert(Name::new("Ground"));
 
-    let default_collider_color = materials.add(Color::rgba(0.3, 0.5, 0.3, 0.3).into());
-    let selected_collider_color = materials.add(Color::rgba(0.3, 0.9, 0.3, 0.9).into());
-
-    for i in 0..10 {
-        for j in 0..2 {
-            commands
-                .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
-                    4.0 * i as f32 + j as f32,
-                    0.8,
-                    8.0 * j as f32,
-                )))
-                .insert(Name::new("Tower_Base"))
-                .insert(meshes.add(shape::Capsule::default().into()))
-                .insert(Highlighting {
-                    initial: default_collider_color.clone(),
-                    hovered: Some(selected_collider_color.clone()),
-                    pressed: Some(selected_collider_color.clone()),
-                    selected: Some(selected_collider_color.clone()),
-                })
-                .insert(default_collider_color.clone())
-                .insert(NotShadowCaster)
-                .insert_bundle(PickableBundle::default())
-                .with_children(|commands| {
-                    commands.spawn_bundle(SceneBundle {
-                        scene: game_assets.tower_base_scene.clone(),
-                        transform: Transform::from_xyz(0.0, -0.8, 0.0),
-                        ..Default::default()
-                    });
-                });
-        }
-    }
-
-    for i in 1..25 {
-        commands
-            .spawn_bundle(SceneBundle {
-                scene: game_assets.target_scene.clone(),
-                transform: Transform::from_xyz(-4.0 * i as f32, 0.4, 2.5),
-                ..Default::default()
-            })
-            .insert(Target { speed: 0.6 })
-            .insert(Health { value: 5 })
-            .insert(Name::new("Target"));
+    for (slot, position) in level.slots.iter().enumerate() {
+        spawn_build_slot(&mut commands, *position, slot);
     }
 
     commands
@@ -151,6 +230,53 @@ fn spawn_basic_scene(
         .insert(Name::new("Light"));
 }
 
+/// Makes new build slots pickable, including those of a loaded game and those a tower was built
+/// on, and gives empty ones their base model.
+fn decorate_build_slots(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    game_assets: Res<GameAssets>,
+    slots: Query<(Entity, Option<&Tower>), Added<BuildSlot>>,
+    mut colors: Local<Option<(Handle<StandardMaterial>, Handle<StandardMaterial>)>>,
+) {
+    if slots.is_empty() {
+        return;
+    }
+    let (default_collider_color, selected_collider_color) = colors
+        .get_or_insert_with(|| {
+            (
+                materials.add(Color::rgba(0.3, 0.5, 0.3, 0.3).into()),
+                materials.add(Color::rgba(0.3, 0.9, 0.3, 0.9).into()),
+            )
+        })
+        .clone();
+    for (slot, tower) in &slots {
+        let mut slot = commands.entity(slot);
+        slot
+            .insert(meshes.add(shape::Capsule::default().into()))
+            .insert(Highlighting {
+                initial: default_collider_color.clone(),
+                hovered: Some(selected_collider_color.clone()),
+                pressed: Some(selected_collider_color.clone()),
+                selected: Some(selected_collider_color.clone()),
+            })
+            .insert(default_collider_color.clone())
+            .insert(NotShadowCaster)
+            .insert_bundle(PickableBundle::default());
+        // Towers bring their own model.
+        if tower.is_none() {
+            slot.with_children(|commands| {
+                commands.spawn_bundle(SceneBundle {
+                    scene: game_assets.tower_base_scene.clone(),
+                    transform: Transform::from_xyz(0.0, -0.8, 0.0),
+                    ..Default::default()
+                });
+            });
+        }
+    }
+}
+
 fn spawn_camera(mut commands: Commands) {
     commands
         .spawn_bundle(Camera3dBundle {
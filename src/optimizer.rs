@@ -0,0 +1,239 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+
+use crate::*;
+
+/// The tower type built on each slot of a level, `None` for slots left empty.
+pub type Layout = Vec<Option<TowerType>>;
+
+/// How well a layout did: fewer leaks first, then a faster clear.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct LayoutScore {
+    pub leaked: u32,
+    pub total_seconds: f64,
+}
+
+impl LayoutScore {
+    fn from_report(report: &BalanceReport) -> Self {
+        LayoutScore {
+            leaked: report.waves.iter().map(|wave| wave.leaked).sum(),
+            total_seconds: report.total_seconds,
+        }
+    }
+
+    /// Orders scores so that the better one is greater.
+    pub fn cmp_quality(&self, other: &LayoutScore) -> Ordering {
+        other
+            .leaked
+            .cmp(&self.leaked)
+            .then(other.total_seconds.total_cmp(&self.total_seconds))
+    }
+}
+
+/// Random-restart hill climbing over tower layouts that fit a gold budget.
+#[derive(Debug, Clone)]
+pub struct Optimizer {
+    pub level: Level,
+    pub difficulty: Difficulty,
+    /// Seeds both the search and the simulated games.
+    pub seed: u64,
+    /// Gold available to build the whole layout before the first wave.
+    pub budget: u32,
+    pub restarts: u32,
+    /// Neighbours tried in a row without improvement before restarting.
+    pub patience: u32,
+    /// Simulated seconds after which a game is given up on.
+    pub max_seconds: f64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LayoutReport {
+    pub score: LayoutScore,
+    pub cost: u32,
+    pub towers: Vec<PlannedTower>,
+    pub tower_counts: Vec<(TowerType, usize)>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct OptimizerReport {
+    pub level: String,
+    pub seed: u64,
+    pub budget: u32,
+    pub evaluations: usize,
+    /// Best layouts found, best first.
+    pub layouts: Vec<LayoutReport>,
+}
+
+impl Optimizer {
+    pub fn run(&self, keep: usize) -> OptimizerReport {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut scores: HashMap<Layout, LayoutScore> = HashMap::new();
+
+        let mut restarts = self.restarts;
+        if self.level.slots.is_empty() {
+            warn!("Level {} has no build slots, so there is no layout to search", self.level.name);
+            self.evaluate(&Vec::new(), &mut scores);
+            restarts = 0;
+        }
+        for restart in 0..restarts {
+            let mut current = self.random_layout(&mut rng);
+            let mut current_score = self.evaluate(&current, &mut scores);
+            let mut stale = 0;
+            while stale < self.patience {
+                let candidate = self.neighbour(&current, &mut rng);
+                let score = self.evaluate(&candidate, &mut scores);
+                if score.cmp_quality(&current_score) == Ordering::Greater {
+                    current = candidate;
+                    current_score = score;
+                    stale = 0;
+                } else {
+                    stale += 1;
+                }
+            }
+            info!(
+                "Restart {} ended with {} leaked in {:.1}s",
+                restart, current_score.leaked, current_score.total_seconds
+            );
+        }
+
+        let evaluations = scores.len();
+        let mut ranked: Vec<(Layout, LayoutScore)> = scores.into_iter().collect();
+        ranked.sort_by(|(a_layout, a), (b_layout, b)| {
+            b.cmp_quality(a).then_with(|| a_layout.cmp(b_layout))
+        });
+        OptimizerReport {
+            level: self.level.name.clone(),
+            seed: self.seed,
+            budget: self.budget,
+            evaluations,
+            layouts: ranked
+                .into_iter()
+                .take(keep)
+                .map(|(layout, score)| self.layout_report(&layout, score))
+                .collect(),
+        }
+    }
+
+    fn cost(layout: &Layout) -> u32 {
+        layout.iter().flatten().map(TowerType::cost).sum()
+    }
+
+    fn plan(layout: &Layout) -> PlacementPlan {
+        PlacementPlan {
+            towers: layout
+                .iter()
+                .enumerate()
+                .filter_map(|(slot, tower_type)| {
+                    tower_type.map(|tower_type| PlannedTower { slot, tower_type })
+                })
+                .collect(),
+        }
+    }
+
+    fn evaluate(&self, layout: &Layout, scores: &mut HashMap<Layout, LayoutScore>) -> LayoutScore {
+        if let Some(score) = scores.get(layout) {
+            return *score;
+        }
+        let report = BalanceRun {
+            level: self.level.clone(),
+            plan: Self::plan(layout),
+            difficulty: self.difficulty,
+            seed: self.seed,
+            max_seconds: self.max_seconds,
+            starting_gold: Some(self.budget),
+        }
+        .simulate();
+        let score = LayoutScore::from_report(&report);
+        scores.insert(layout.clone(), score);
+        score
+    }
+
+    /// Fills random slots with random towers until nothing more fits the budget.
+    fn random_layout(&self, rng: &mut ChaCha8Rng) -> Layout {
+        let mut layout = vec![None; self.level.slots.len()];
+        let mut slots: Vec<usize> = (0..layout.len()).collect();
+        slots.shuffle(rng);
+        for slot in slots {
+            let tower_type = *TowerType::ALL.choose(rng).unwrap();
+            if Self::cost(&layout) + tower_type.cost() <= self.budget {
+                layout[slot] = Some(tower_type);
+            }
+        }
+        layout
+    }
+
+    /// Changes, adds, removes or moves one tower, keeping within the budget.
+    /// Gives back the same layout if no change is found, e.g. when nothing fits the budget or
+    /// the level has no slots.
+    fn neighbour(&self, layout: &Layout, rng: &mut ChaCha8Rng) -> Layout {
+        if layout.is_empty() {
+            return Vec::new();
+        }
+        for _ in 0..100 {
+            let mut candidate = layout.clone();
+            let slot = rng.gen_range(0..candidate.len());
+            match rng.gen_range(0..3) {
+                0 => candidate[slot] = Some(*TowerType::ALL.choose(rng).unwrap()),
+                1 => candidate[slot] = None,
+                _ => candidate.swap(slot, rng.gen_range(0..layout.len())),
+            }
+            if candidate != *layout && Self::cost(&candidate) <= self.budget {
+                return candidate;
+            }
+        }
+        layout.clone()
+    }
+
+    fn layout_report(&self, layout: &Layout, score: LayoutScore) -> LayoutReport {
+        let tower_counts = TowerType::ALL
+            .into_iter()
+            .map(|tower_type| {
+                let count = layout.iter().filter(|built| **built == Some(tower_type)).count();
+                (tower_type, count)
+            })
+            .collect();
+        LayoutReport {
+            score,
+            cost: Self::cost(layout),
+            towers: Self::plan(layout).towers,
+            tower_counts,
+        }
+    }
+}
+
+impl OptimizerReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("optimizer reports always serialize")
+    }
+
+    /// One row per layout, with the tower type of every slot (empty when nothing is built).
+    pub fn to_csv(&self, slots: usize) -> String {
+        let mut csv = String::from("rank,leaked,total_seconds,cost");
+        for slot in 0..slots {
+            csv += &format!(",slot_{}", slot);
+        }
+        csv += "\n";
+        for (rank, layout) in self.layouts.iter().enumerate() {
+            csv += &format!(
+                "{},{},{:.3},{}",
+                rank + 1,
+                layout.score.leaked,
+                layout.score.total_seconds,
+                layout.cost
+            );
+            for slot in 0..slots {
+                let tower = layout.towers.iter().find(|tower| tower.slot == slot);
+                match tower {
+                    Some(tower) => csv += &format!(",{:?}", tower.tower_type),
+                    None => csv += ",",
+                }
+            }
+            csv += "\n";
+        }
+        csv
+    }
+}
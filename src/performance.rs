@@ -0,0 +1,315 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Seconds of frames summarized by each `PerformanceReport`.
+pub const REPORT_INTERVAL: f64 = 1.0;
+
+/// Frame times, entity counts and gameplay system timings, refreshed every `REPORT_INTERVAL`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PerformanceReport {
+    /// Seconds since startup when the report was made.
+    pub time: f64,
+    pub frames: usize,
+    pub fps: f64,
+    /// Frame time percentiles, in milliseconds.
+    pub frame_p50: f64,
+    pub frame_p95: f64,
+    pub frame_p99: f64,
+    pub frame_max: f64,
+    /// Simulation ticks run since the previous report.
+    pub ticks: u32,
+    pub bullets: usize,
+    pub targets: usize,
+    pub towers: usize,
+    /// Average time per tick of each gameplay system in microseconds, in the order they run.
+    pub systems: Vec<(SimulationSet, &'static str, f64)>,
+}
+
+impl PerformanceReport {
+    /// Fills in the frame statistics of `frames`, which took `seconds` in total.
+    pub fn with_frames(mut self, frames: &[Duration], seconds: f64) -> Self {
+        let mut millis: Vec<f64> = frames.iter().map(|frame| frame.as_secs_f64() * 1000.0).collect();
+        millis.sort_by(f64::total_cmp);
+        let percentile = |p: f64| match millis.len() {
+            0 => 0.0,
+            len => millis[((len - 1) as f64 * p).round() as usize],
+        };
+        self.frames = millis.len();
+        self.fps = if seconds > 0.0 { millis.len() as f64 / seconds } else { 0.0 };
+        self.frame_p50 = percentile(0.5);
+        self.frame_p95 = percentile(0.95);
+        self.frame_p99 = percentile(0.99);
+        self.frame_max = percentile(1.0);
+        self
+    }
+
+    /// The gameplay systems that took longest per tick, slowest first.
+    pub fn slowest_systems(&self, count: usize) -> Vec<(SimulationSet, &'static str, f64)> {
+        let mut systems = self.systems.clone();
+        systems.sort_by(|a, b| b.2.total_cmp(&a.2));
+        systems.truncate(count);
+        systems
+    }
+
+    /// One line for the log.
+    pub fn summary(&self) -> String {
+        let slowest: Vec<String> = self
+            .slowest_systems(3)
+            .iter()
+            .map(|(_, name, micros)| format!("{} {:.1}µs", name, micros))
+            .collect();
+        format!(
+            "{:.0} fps, frame p50 {:.2}ms p95 {:.2}ms p99 {:.2}ms max {:.2}ms, {} ticks, \
+             {} bullets, {} targets, {} towers, slowest systems: {}",
+            self.fps,
+            self.frame_p50,
+            self.frame_p95,
+            self.frame_p99,
+            self.frame_max,
+            self.ticks,
+            self.bullets,
+            self.targets,
+            self.towers,
+            slowest.join(", ")
+        )
+    }
+
+    pub fn csv_header(&self) -> String {
+        let mut columns = vec![
+            "time".to_string(),
+            "fps".to_string(),
+            "frame_p50_ms".to_string(),
+            "frame_p95_ms".to_string(),
+            "frame_p99_ms".to_string(),
+            "frame_max_ms".to_string(),
+            "ticks".to_string(),
+            "bullets".to_string(),
+            "targets".to_string(),
+            "towers".to_string(),
+        ];
+        columns.extend(
+            self.systems
+                .iter()
+                .map(|(set, name, _)| format!("{}_{}_us", format!("{:?}", set).to_lowercase(), name)),
+        );
+        columns.join(",")
+    }
+
+    pub fn csv_row(&self) -> String {
+        let mut columns = vec![
+            format!("{:.3}", self.time),
+            format!("{:.1}", self.fps),
+            format!("{:.3}", self.frame_p50),
+            format!("{:.3}", self.frame_p95),
+            format!("{:.3}", self.frame_p99),
+            format!("{:.3}", self.frame_max),
+            self.ticks.to_string(),
+            self.bullets.to_string(),
+            self.targets.to_string(),
+            self.towers.to_string(),
+        ];
+        columns.extend(self.systems.iter().map(|(_, _, micros)| format!("{:.2}", micros)));
+        columns.join(",")
+    }
+}
+
+/// Where reports go besides the overlay: a line in the log and/or a row in a CSV file.
+#[derive(Default)]
+pub struct PerformanceLog {
+    log: bool,
+    csv: Option<BufWriter<File>>,
+    header_written: bool,
+}
+
+impl PerformanceLog {
+    /// Appends to the `csv` file, writing the header first only if the file is new or empty.
+    pub fn new(log: bool, csv: Option<&Path>) -> io::Result<Self> {
+        let csv = csv
+            .map(|path| OpenOptions::new().append(true).create(true).open(path))
+            .transpose()?;
+        let header_written = match &csv {
+            Some(file) => file.metadata()?.len() > 0,
+            None => false,
+        };
+        Ok(PerformanceLog {
+            log,
+            csv: csv.map(BufWriter::new),
+            header_written,
+        })
+    }
+
+    pub fn write(&mut self, report: &PerformanceReport) -> io::Result<()> {
+        if self.log {
+            info!("{}", report.summary());
+        }
+        if let Some(csv) = &mut self.csv {
+            if !self.header_written {
+                writeln!(csv, "{}", report.csv_header())?;
+                self.header_written = true;
+            }
+            writeln!(csv, "{}", report.csv_row())?;
+            csv.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the `PerformanceReport`, and writes it to the `PerformanceLog` if there is one.
+pub struct PerformancePlugin;
+
+impl Plugin for PerformancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PerformanceReport>()
+            .add_system_to_stage(CoreStage::Last, collect_performance)
+            .add_system_to_stage(CoreStage::Last, write_performance_log.after(collect_performance));
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+#[derive(Default)]
+struct FrameTimes {
+    frames: Vec<Duration>,
+    since: f64,
+}
+
+type EntityCounts<'w, 's> = (
+    Query<'w, 's, (), With<Bullet>>,
+    Query<'w, 's, (), With<Target>>,
+    Query<'w, 's, (), With<Tower>>,
+);
+
+fn collect_performance(
+    time: Res<Time>,
+    mut frames: Local<FrameTimes>,
+    mut timings: ResMut<SystemTimings>,
+    mut report: ResMut<PerformanceReport>,
+    (bullets, targets, towers): EntityCounts,
+) {
+    if !time.delta().is_zero() {
+        frames.frames.push(time.delta());
+    }
+    let now = time.seconds_since_startup();
+    if now - frames.since < REPORT_INTERVAL {
+        return;
+    }
+    let (ticks, systems) = timings.take();
+    *report = PerformanceReport {
+        time: now,
+        ticks,
+        bullets: bullets.iter().count(),
+        targets: targets.iter().count(),
+        towers: towers.iter().count(),
+        systems: systems
+            .into_iter()
+            .map(|(set, name, time)| (set, name, time.as_secs_f64() * 1e6))
+            .collect(),
+        ..default()
+    }
+    .with_frames(&frames.frames, now - frames.since);
+    frames.frames.clear();
+    frames.since = now;
+}
+
+fn write_performance_log(report: Res<PerformanceReport>, log: Option<ResMut<PerformanceLog>>) {
+    let mut log = match log {
+        Some(log) if report.is_changed() && report.frames > 0 => log,
+        _ => return,
+    };
+    if let Err(err) = log.write(&report) {
+        warn!("Could not write the performance log: {}", err);
+        log.csv = None;
+    }
+}
+
+#[derive(Component)]
+pub struct PerformanceOverlay;
+
+/// Shows the latest `PerformanceReport` in the top-left corner, toggled with
+/// `Action::TogglePerformance`. Only used when the game runs with a window.
+pub struct PerformanceOverlayPlugin;
+
+impl Plugin for PerformanceOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_performance_overlay)
+            .add_system(toggle_performance_overlay)
+            .add_system(update_performance_overlay);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn spawn_performance_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 14.0,
+        color: Color::rgba(1.0, 1.0, 1.0, 0.8),
+    };
+    commands
+        .spawn_bundle(
+            TextBundle::from_section("", style).with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(8.0),
+                    top: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(Visibility { is_visible: false })
+        .insert(PerformanceOverlay);
+}
+
+pub fn toggle_performance_overlay(
+    input: ActionInput,
+    mut overlays: Query<&mut Visibility, With<PerformanceOverlay>>,
+) {
+    if input.just_pressed(Action::TogglePerformance) {
+        for mut visibility in &mut overlays {
+            visibility.is_visible = !visibility.is_visible;
+        }
+    }
+}
+
+fn update_performance_overlay(
+    report: Res<PerformanceReport>,
+    mut overlays: Query<&mut Text, With<PerformanceOverlay>>,
+) {
+    if !report.is_changed() {
+        return;
+    }
+    let mut lines = vec![
+        format!("{:.0} FPS", report.fps),
+        format!(
+            "frame p50 {:.1} ms  p95 {:.1} ms  p99 {:.1} ms  max {:.1} ms",
+            report.frame_p50, report.frame_p95, report.frame_p99, report.frame_max
+        ),
+        format!(
+            "{} bullets  {} targets  {} towers",
+            report.bullets, report.targets, report.towers
+        ),
+        format!("per tick ({} ticks):", report.ticks),
+    ];
+    lines.extend(
+        report
+            .systems
+            .iter()
+            .map(|(set, name, micros)| format!("  {:?} {} {:.1} µs", set, name, micros)),
+    );
+    for mut text in &mut overlays {
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+pub const STARTING_GOLD: u32 = 100;
+pub const STARTING_LIVES: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Player {
+    pub gold: u32,
+    pub lives: u32,
+}
+
+impl Player {
+    pub fn new(modifiers: &DifficultyModifiers) -> Self {
+        Player {
+            gold: modifiers.gold(STARTING_GOLD),
+            lives: modifiers.lives(STARTING_LIVES),
+        }
+    }
+}
+
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_startup_system_to_stage(StartupStage::PreStartup, spawn_player)
+            .add_simulation_system(SimulationSet::Cleanup, award_bounties)
+            .add_simulation_system(SimulationSet::Cleanup, lose_lives);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn spawn_player(mut commands: Commands, difficulty: Res<Difficulty>) {
+    commands.insert_resource(Player::new(&difficulty.modifiers()));
+}
+
+fn award_bounties(mut killed: EventReader<TargetKilled>, mut player: ResMut<Player>) {
+    for event in killed.iter() {
+        player.gold += event.target.bounty;
+    }
+}
+
+fn lose_lives(mut leaked: EventReader<TargetLeaked>, mut player: ResMut<Player>) {
+    for _ in leaked.iter() {
+        player.lives = player.lives.saturating_sub(1);
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::*;
+
+/// Marks a bullet entity as owned by the `ProjectilePool`, with the kind of bullet its scene shows.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PooledBullet(pub TowerType);
+
+/// Counters of the `ProjectilePool`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PoolMetrics {
+    /// Bullet entities spawned because no idle one was left.
+    pub created: u32,
+    /// Shots fired with an idle bullet instead of a new one.
+    pub reused: u32,
+    /// Bullets waiting to be fired again.
+    pub idle: usize,
+}
+
+/// Hidden bullet entities kept around once they hit or expire, so firing does not have to
+/// instantiate a bullet scene on every shot.
+///
+/// Idle bullets have no `Bullet` or `Lifetime` component, so the bullet systems skip them.
+#[derive(Debug, Default)]
+pub struct ProjectilePool {
+    idle: BTreeMap<TowerType, Vec<Entity>>,
+    /// Every idle entity, so a bullet released twice in a tick is only pooled once.
+    idle_entities: HashSet<Entity>,
+    created: u32,
+    reused: u32,
+}
+
+impl ProjectilePool {
+    pub fn metrics(&self) -> PoolMetrics {
+        PoolMetrics {
+            created: self.created,
+            reused: self.reused,
+            idle: self.idle_entities.len(),
+        }
+    }
+
+    /// Number of idle bullets of `tower_type`.
+    pub fn idle(&self, tower_type: TowerType) -> usize {
+        self.idle.get(&tower_type).map_or(0, Vec::len)
+    }
+
+    /// Takes an idle bullet of `tower_type` out of the pool, if there is one.
+    pub fn acquire(&mut self, tower_type: TowerType) -> Option<Entity> {
+        let entity = self.idle.get_mut(&tower_type)?.pop()?;
+        self.idle_entities.remove(&entity);
+        self.reused += 1;
+        Some(entity)
+    }
+
+    /// Counts a bullet spawned because the pool had none to give.
+    pub fn record_created(&mut self) {
+        self.created += 1;
+    }
+
+    /// Hides `bullet` and keeps it for a later shot.
+    pub fn release(&mut self, commands: &mut Commands, bullet: Entity, kind: PooledBullet) {
+        if !self.idle_entities.insert(bullet) {
+            return;
+        }
+        self.idle.entry(kind.0).or_default().push(bullet);
+        commands
+            .entity(bullet)
+            .remove::<Bullet>()
+            .remove::<Lifetime>()
+            .insert(Visibility { is_visible: false });
+    }
+}
@@ -0,0 +1,168 @@
+use std::{collections::VecDeque, fs, path::PathBuf};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Seconds between two writes of a replay being recorded.
+pub const REPLAY_FLUSH_INTERVAL: f64 = 5.0;
+
+/// Bumped whenever the format or the simulation changes, as older replays would play out differently.
+pub const REPLAY_VERSION: u32 = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedAction {
+    pub tick: u64,
+    pub action: PlayerAction,
+}
+
+/// Everything needed to reproduce a game: its starting conditions and every player action
+/// with the simulation tick it was applied on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub level: Level,
+    pub actions: Vec<RecordedAction>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, difficulty: Difficulty, level: Level) -> Self {
+        Replay {
+            version: REPLAY_VERSION,
+            seed,
+            difficulty,
+            level,
+            actions: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &PathBuf) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let replay: Replay = ron::from_str(&source).map_err(|err| err.to_string())?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "replay version {} is not supported (expected {})",
+                replay.version, REPLAY_VERSION
+            ));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<(), String> {
+        let source = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| err.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+        fs::write(path, source).map_err(|err| err.to_string())
+    }
+}
+
+/// Records applied actions in memory. `flush_replay` writes them out every
+/// `REPLAY_FLUSH_INTERVAL` and when the game exits, so a crash loses at most the last few seconds
+/// without slowing down the simulation tick.
+pub struct ReplayRecorder {
+    path: PathBuf,
+    replay: Replay,
+    unsaved: bool,
+    last_flush: f64,
+}
+
+impl ReplayRecorder {
+    pub fn new(path: PathBuf, seed: u64, difficulty: Difficulty, level: Level) -> Self {
+        ReplayRecorder {
+            path,
+            replay: Replay::new(seed, difficulty, level),
+            unsaved: true,
+            last_flush: 0.0,
+        }
+    }
+
+    /// `last-replay.ron` next to the settings file.
+    pub fn default_path() -> Option<PathBuf> {
+        Settings::path().map(|path| path.with_file_name("last-replay.ron"))
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn record(&mut self, tick: u64, actions: &[PlayerAction]) {
+        self.replay
+            .actions
+            .extend(actions.iter().map(|action| RecordedAction {
+                tick,
+                action: *action,
+            }));
+        self.unsaved = true;
+    }
+
+    /// Writes the replay file if actions were recorded since the last write.
+    pub fn flush(&mut self) {
+        if !self.unsaved {
+            return;
+        }
+        self.unsaved = false;
+        if let Err(err) = self.replay.save(&self.path) {
+            warn!("Could not write replay {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+impl Drop for ReplayRecorder {
+    /// Keeps the actions recorded so far when a loaded game replaces the recorder.
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Writes the replay being recorded every `REPLAY_FLUSH_INTERVAL` and on exit.
+pub fn flush_replay(
+    recorder: Option<ResMut<ReplayRecorder>>,
+    time: Res<Time>,
+    mut exit: EventReader<AppExit>,
+) {
+    let mut recorder = match recorder {
+        Some(recorder) => recorder,
+        None => return,
+    };
+    let now = time.seconds_since_startup();
+    let exiting = exit.iter().count() > 0;
+    if exiting || now - recorder.last_flush >= REPLAY_FLUSH_INTERVAL {
+        recorder.last_flush = now;
+        recorder.flush();
+    }
+}
+
+/// Feeds the actions of a replay back into the simulation on their recorded ticks.
+pub struct ReplayPlayback {
+    actions: VecDeque<RecordedAction>,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: &Replay) -> Self {
+        let mut actions: Vec<RecordedAction> = replay.actions.clone();
+        actions.sort_by_key(|recorded| recorded.tick);
+        ReplayPlayback {
+            actions: actions.into(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn take_due(&mut self, tick: u64) -> Vec<PlayerAction> {
+        let mut due = Vec::new();
+        while let Some(recorded) = self.actions.front() {
+            if recorded.tick > tick {
+                break;
+            }
+            due.push(self.actions.pop_front().unwrap().action);
+        }
+        due
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// Independent random streams, one per subsystem, so that drawing numbers in one
+/// subsystem never changes the sequence another subsystem sees.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RngStream {
+    Spawning,
+    Combat,
+    Level,
+    AutoPlayer,
+}
+
+impl RngStream {
+    fn id(&self) -> u64 {
+        match self {
+            RngStream::Spawning => 0,
+            RngStream::Combat => 1,
+            RngStream::Level => 2,
+            RngStream::AutoPlayer => 3,
+        }
+    }
+}
+
+/// The only source of randomness for gameplay. Only use it from the simulation stage,
+/// otherwise the frame rate decides how many numbers are drawn per tick.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameRng {
+    seed: u64,
+    streams: BTreeMap<RngStream, ChaCha8Rng>,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(rand::thread_rng().gen())
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            streams: BTreeMap::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        let seed = self.seed;
+        self.streams.entry(stream).or_insert_with(|| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream.id());
+            rng
+        })
+    }
+}
+
+pub struct GameRngPlugin;
+
+impl Plugin for GameRngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>()
+            .add_startup_system(log_seed);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn log_seed(rng: Res<GameRng>) {
+    info!("Game seed: {}", rng.seed());
+}
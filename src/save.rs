@@ -0,0 +1,325 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+pub const SAVE_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedTower {
+    pub slot: usize,
+    pub tower_type: TowerType,
+    /// Time since the tower last fired.
+    pub shot_elapsed: Duration,
+    pub stats: TowerStats,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedTarget {
+    pub position: Vec3,
+    pub target: Target,
+    pub health: Health,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedBullet {
+    pub tower_type: TowerType,
+    /// Slot of the tower that fired it, if that tower is still standing.
+    pub source: Option<usize>,
+    pub translation: Vec3,
+    pub direction: Vec3,
+    pub speed: f32,
+    #[serde(default)]
+    pub travelled: Vec3,
+    pub lifetime_elapsed: Duration,
+}
+
+/// Everything needed to continue a game where it was left.
+///
+/// Build slots are not stored: every slot of the level without a saved tower is free.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SavedGame {
+    pub version: u32,
+    pub tick: u64,
+    pub level: Level,
+    pub difficulty: Difficulty,
+    pub rng: GameRng,
+    pub player: Player,
+    pub waves: WaveState,
+    pub stats: GameStats,
+    pub towers: Vec<SavedTower>,
+    pub targets: Vec<SavedTarget>,
+    pub bullets: Vec<SavedBullet>,
+}
+
+impl SavedGame {
+    /// `quicksave.ron` next to the settings file.
+    pub fn quicksave_path() -> Option<PathBuf> {
+        Settings::path().map(|path| path.with_file_name("quicksave.ron"))
+    }
+
+    /// `autosave.ron` next to the settings file.
+    pub fn autosave_path() -> Option<PathBuf> {
+        Settings::path().map(|path| path.with_file_name("autosave.ron"))
+    }
+
+    /// Takes the state of the game as of the last simulated tick.
+    pub fn capture(world: &mut World) -> Self {
+        let mut towers: Vec<SavedTower> = world
+            .query::<(&BuildSlot, &TowerType, &Tower, &TowerStats)>()
+            .iter(world)
+            .map(|(slot, tower_type, tower, stats)| SavedTower {
+                slot: slot.0,
+                tower_type: *tower_type,
+                shot_elapsed: tower.shooting_timer.elapsed(),
+                stats: *stats,
+            })
+            .collect();
+        towers.sort_by_key(|tower| tower.slot);
+
+        let targets = world
+            .query::<(&Target, &Health, &Interpolated)>()
+            .iter(world)
+            .map(|(target, health, interpolated)| SavedTarget {
+                position: interpolated.current(),
+                target: *target,
+                health: *health,
+            })
+            .collect();
+
+        let mut bullets = Vec::new();
+        let mut bullet_query = world.query::<(&Bullet, &PooledBullet, &Lifetime, &Interpolated)>();
+        for (bullet, kind, lifetime, interpolated) in bullet_query.iter(world) {
+            let source = bullet
+                .source
+                .and_then(|tower| world.get::<BuildSlot>(tower))
+                .map(|slot| slot.0);
+            bullets.push(SavedBullet {
+                tower_type: kind.0,
+                source,
+                translation: interpolated.current(),
+                direction: bullet.direction,
+                speed: bullet.speed,
+                travelled: bullet.travelled,
+                lifetime_elapsed: lifetime.timer.elapsed(),
+            });
+        }
+
+        SavedGame {
+            version: SAVE_VERSION,
+            tick: world.resource::<SimulationClock>().tick(),
+            level: world.resource::<Level>().clone(),
+            difficulty: *world.resource::<Difficulty>(),
+            rng: world.resource::<GameRng>().clone(),
+            player: *world.resource::<Player>(),
+            waves: world.resource::<WaveState>().clone(),
+            stats: world.resource::<GameStats>().clone(),
+            towers,
+            targets,
+            bullets,
+        }
+    }
+
+    /// Replaces the game in `world` with the saved one.
+    ///
+    /// A replay being recorded or played back cannot continue from a loaded game, so both stop.
+    pub fn restore(self, world: &mut World) {
+        let mut stale = Vec::new();
+        stale.extend(world.query_filtered::<Entity, With<BuildSlot>>().iter(world));
+        stale.extend(world.query_filtered::<Entity, With<Tower>>().iter(world));
+        stale.extend(world.query_filtered::<Entity, With<Target>>().iter(world));
+        stale.extend(world.query_filtered::<Entity, With<Bullet>>().iter(world));
+        for entity in stale {
+            if world.get_entity(entity).is_some() {
+                despawn_with_children_recursive(world, entity);
+            }
+        }
+
+        world.resource_mut::<SimulationClock>().restore_tick(self.tick);
+        world.insert_resource(self.difficulty);
+        world.insert_resource(self.rng);
+        world.insert_resource(self.player);
+        world.insert_resource(self.waves);
+        world.insert_resource(self.stats);
+        world.insert_resource(ActionQueue::default());
+        world.remove_resource::<ReplayRecorder>();
+        world.remove_resource::<ReplayPlayback>();
+
+        let mut pool = world.remove_resource::<ProjectilePool>().unwrap_or_default();
+        let mut queue = CommandQueue::default();
+        let assets = world.resource::<GameAssets>();
+        let mut commands = Commands::new(&mut queue, world);
+        let mut towers = Vec::new();
+        for (slot, position) in self.level.slots.iter().enumerate() {
+            match self.towers.iter().find(|tower| tower.slot == slot) {
+                Some(saved) => {
+                    let tower = spawn_tower(&mut commands, assets, *position, saved.tower_type);
+                    let (_, mut state) = saved.tower_type.get_tower(assets);
+                    state.shooting_timer.set_elapsed(saved.shot_elapsed);
+                    commands
+                        .entity(tower)
+                        .insert(state)
+                        .insert(saved.stats)
+                        .insert(BuildSlot(slot));
+                    towers.push((slot, tower));
+                }
+                None => {
+                    spawn_build_slot(&mut commands, *position, slot);
+                }
+            }
+        }
+        for saved in &self.targets {
+            spawn_target(&mut commands, assets, saved.position, saved.target, saved.health);
+        }
+        for saved in &self.bullets {
+            let (scene, mut bullet) = saved.tower_type.get_bullet(saved.direction, assets);
+            bullet.speed = saved.speed;
+            bullet.travelled = saved.travelled;
+            bullet.source = saved
+                .source
+                .and_then(|source| towers.iter().find(|(slot, _)| *slot == source))
+                .map(|(_, tower)| *tower);
+            let mut lifetime = bullet_lifetime();
+            lifetime.timer.set_elapsed(saved.lifetime_elapsed);
+            let entity = spawn_bullet(&mut commands, &mut pool, saved.tower_type, scene, saved.translation, bullet);
+            commands.entity(entity).insert(lifetime);
+        }
+        queue.apply(world);
+        world.insert_resource(pool);
+        world.insert_resource(self.level);
+    }
+
+    pub fn load(path: &PathBuf) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let game: SavedGame = ron::from_str(&source).map_err(|err| err.to_string())?;
+        if game.version != SAVE_VERSION {
+            return Err(format!(
+                "save version {} is not supported (expected {})",
+                game.version, SAVE_VERSION
+            ));
+        }
+        Ok(game)
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<(), String> {
+        let source = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| err.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+        fs::write(path, source).map_err(|err| err.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SaveRequest {
+    Save(PathBuf),
+    Load(PathBuf),
+    /// Restores a game read beforehand, e.g. by `--load` so a bad file stops the game from starting.
+    Restore(Box<SavedGame>),
+}
+
+/// Saves and loads to carry out between two frames.
+#[derive(Default, Debug)]
+pub struct SaveRequests(Vec<SaveRequest>);
+
+impl SaveRequests {
+    pub fn push(&mut self, request: SaveRequest) {
+        self.0.push(request);
+    }
+}
+
+/// Carries out `SaveRequests`. Quick save keys and autosaves are added by the windowed game.
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveRequests>()
+            .add_system_to_stage(CoreStage::PreUpdate, process_save_requests.exclusive_system());
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn process_save_requests(world: &mut World) {
+    let requests = std::mem::take(&mut world.resource_mut::<SaveRequests>().0);
+    for request in requests {
+        match request {
+            SaveRequest::Save(path) => match SavedGame::capture(world).save(&path) {
+                Ok(()) => info!("Saved the game to {}", path.display()),
+                Err(err) => warn!("Could not save the game to {}: {}", path.display(), err),
+            },
+            SaveRequest::Load(path) => match SavedGame::load(&path) {
+                Ok(game) => {
+                    game.restore(world);
+                    info!("Loaded the game from {}", path.display());
+                }
+                Err(err) => warn!("Could not load the game from {}: {}", path.display(), err),
+            },
+            SaveRequest::Restore(game) => {
+                game.restore(world);
+                info!("Restored the saved game");
+            }
+        }
+    }
+}
+
+/// Quick saves and loads on the bound keys.
+pub fn quick_save_keys(input: ActionInput, mut requests: ResMut<SaveRequests>) {
+    let path = match SavedGame::quicksave_path() {
+        Some(path) => path,
+        None => return,
+    };
+    if input.just_pressed(Action::QuickSave) {
+        requests.push(SaveRequest::Save(path.clone()));
+    }
+    if input.just_pressed(Action::QuickLoad) {
+        requests.push(SaveRequest::Load(path));
+    }
+}
+
+/// Where `autosave_between_waves` writes, and the wave it last saved before.
+pub struct Autosave {
+    path: PathBuf,
+    saved_before: Option<usize>,
+}
+
+impl Autosave {
+    pub fn new(path: PathBuf) -> Self {
+        Autosave {
+            path,
+            saved_before: None,
+        }
+    }
+}
+
+/// Saves once each time a wave is cleared and the next one has yet to spawn.
+///
+/// Runs after every simulation tick, after `TickEnd`: with no delay between two waves the gap
+/// lasts a single tick, which a frame simulating several ticks would otherwise skip over.
+pub fn autosave_between_waves(world: &mut World) {
+    let next = world.resource::<WaveState>().next_wave();
+    let waves = world.resource::<Level>().waves.len();
+    let saved_before = match world.get_resource::<Autosave>() {
+        Some(autosave) => autosave.saved_before,
+        None => return,
+    };
+    let between_waves = next > 0
+        && next < waves
+        && saved_before != Some(next)
+        && world.query_filtered::<(), With<Target>>().iter(world).next().is_none();
+    if !between_waves {
+        return;
+    }
+    let game = SavedGame::capture(world);
+    let mut autosave = world.resource_mut::<Autosave>();
+    autosave.saved_before = Some(next);
+    match game.save(&autosave.path) {
+        Ok(()) => info!("Autosaved the game to {}", autosave.path.display()),
+        Err(err) => warn!("Could not autosave the game to {}: {}", autosave.path.display(), err),
+    }
+}
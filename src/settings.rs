@@ -0,0 +1,249 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    window::{WindowMode, WindowResized},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+/// Smallest window size kept in the settings; anything smaller, e.g. the 0x0 of a minimized
+/// window, is not a size to restore.
+pub const MIN_WINDOW_WIDTH: f32 = 320.0;
+pub const MIN_WINDOW_HEIGHT: f32 = 240.0;
+
+/// Seconds without further changes before the settings are written, so a drag-resize
+/// writes the file once.
+pub const SETTINGS_SAVE_DELAY: f64 = 0.5;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub width: f32,
+    pub height: f32,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        WindowSettings {
+            width: 1280.0,
+            height: 720.0,
+            fullscreen: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CameraSettings {
+    pub speed: f32,
+    pub rotate_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            speed: 4.0,
+            rotate_speed: 0.4,
+        }
+    }
+}
+
+/// User preferences and input bindings persisted in the user config directory.
+///
+/// Missing fields fall back to their defaults and out-of-range values are clamped,
+/// so an old or hand-edited file never prevents the game from starting.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub window: WindowSettings,
+    pub camera: CameraSettings,
+    pub volume: f32,
+    pub locale: String,
+    pub ui_scale: f32,
+    pub bindings: InputBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            window: WindowSettings::default(),
+            camera: CameraSettings::default(),
+            volume: 1.0,
+            locale: DEFAULT_LOCALE.to_string(),
+            ui_scale: 1.0,
+            bindings: InputBindings::default(),
+        }
+    }
+}
+
+fn clamp_or(value: f32, min: f32, max: f32, default: f32) -> f32 {
+    if value.is_finite() {
+        value.clamp(min, max)
+    } else {
+        default
+    }
+}
+
+impl Settings {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("bevy-tower-defense").join(SETTINGS_FILE))
+    }
+
+    /// Reads the settings file, falling back to defaults when it is missing. A corrupt file is
+    /// an error, for the caller to report once logging is set up before using the defaults.
+    pub fn load() -> Result<Self, String> {
+        let path = match Settings::path() {
+            Some(path) => path,
+            None => return Ok(Settings::default()),
+        };
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(_) => return Ok(Settings::default()),
+        };
+        ron::from_str::<Settings>(&source)
+            .map(Settings::validated)
+            .map_err(|err| format!("Ignoring corrupt settings file {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self) {
+        let path = match Settings::path() {
+            Some(path) => path,
+            None => return,
+        };
+        let result = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| err.to_string())
+            .and_then(|source| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+                fs::write(&path, source).map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            warn!("Could not write settings file {}: {}", path.display(), err);
+        }
+    }
+
+    /// Clamps every value into a usable range, replacing anything nonsensical with its default.
+    pub fn validated(self) -> Self {
+        let defaults = Settings::default();
+        let locale_valid = !self.locale.is_empty()
+            && self
+                .locale
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        Settings {
+            window: WindowSettings {
+                width: clamp_or(self.window.width, MIN_WINDOW_WIDTH, 7680.0, defaults.window.width),
+                height: clamp_or(self.window.height, MIN_WINDOW_HEIGHT, 4320.0, defaults.window.height),
+                fullscreen: self.window.fullscreen,
+            },
+            camera: CameraSettings {
+                speed: clamp_or(self.camera.speed, 0.1, 50.0, defaults.camera.speed),
+                rotate_speed: clamp_or(
+                    self.camera.rotate_speed,
+                    0.01,
+                    10.0,
+                    defaults.camera.rotate_speed,
+                ),
+            },
+            volume: clamp_or(self.volume, 0.0, 1.0, defaults.volume),
+            locale: if locale_valid {
+                self.locale
+            } else {
+                defaults.locale
+            },
+            ui_scale: clamp_or(self.ui_scale, 0.5, 3.0, defaults.ui_scale),
+            bindings: self.bindings.validated(),
+        }
+    }
+
+    pub fn window_descriptor(&self) -> WindowDescriptor {
+        WindowDescriptor {
+            width: self.window.width,
+            height: self.window.height,
+            mode: if self.window.fullscreen {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            },
+            title: "Bevy Tower Defense".to_string(),
+            resizable: true,
+            ..Default::default()
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_startup_system(report_binding_conflicts)
+            .add_system(sync_locale_setting)
+            .add_system(sync_window_setting)
+            .add_system_to_stage(CoreStage::Last, save_settings);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn report_binding_conflicts(settings: Res<Settings>) {
+    for (binding, actions) in settings.bindings.conflicts() {
+        warn!("{:?} is bound to several actions: {:?}", binding, actions);
+    }
+}
+
+fn sync_locale_setting(locale: Option<Res<Locale>>, mut settings: ResMut<Settings>) {
+    if let Some(locale) = locale {
+        if locale.is_changed() && settings.locale != locale.0 {
+            settings.locale = locale.0.clone();
+        }
+    }
+}
+
+fn sync_window_setting(
+    mut resized: EventReader<WindowResized>,
+    windows: Res<Windows>,
+    mut settings: ResMut<Settings>,
+) {
+    let window = match (resized.iter().last(), windows.get_primary()) {
+        (Some(event), Some(window)) if event.id == window.id() => event,
+        _ => return,
+    };
+    if !settings.window.fullscreen
+        && window.width >= MIN_WINDOW_WIDTH
+        && window.height >= MIN_WINDOW_HEIGHT
+        && (settings.window.width, settings.window.height) != (window.width, window.height)
+    {
+        settings.window.width = window.width;
+        settings.window.height = window.height;
+    }
+}
+
+/// Writes the settings once they have not changed for `SETTINGS_SAVE_DELAY`, or on exit.
+fn save_settings(
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut exit: EventReader<AppExit>,
+    mut changed_at: Local<Option<f64>>,
+) {
+    let now = time.seconds_since_startup();
+    if settings.is_changed() && !settings.is_added() {
+        *changed_at = Some(now);
+    }
+    let exiting = exit.iter().count() > 0;
+    if let Some(at) = *changed_at {
+        if exiting || now - at >= SETTINGS_SAVE_DELAY {
+            *changed_at = None;
+            settings.save();
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    ecs::schedule::{ParallelSystemDescriptorCoercion, ShouldRun},
+    prelude::*,
+    transform::TransformSystem,
+    utils::HashMap,
+};
+
+/// Gameplay ticks per simulated second.
+pub const TICK_RATE: u32 = 60;
+/// Most ticks simulated in one frame; anything beyond is dropped so a long hitch
+/// slows the game down instead of freezing it while it catches up.
+pub const MAX_TICKS_PER_FRAME: u32 = 8;
+
+/// Stage holding every gameplay system. It runs zero or more times per frame,
+/// once per fixed tick, so the simulation never sees a variable delta time.
+///
+/// Systems in this stage run single-threaded, phase by phase in [`SimulationSet`] order,
+/// and within a phase in the order they were added with
+/// [`SimulationAppExt::add_simulation_system`]. This keeps the outcome identical
+/// for identical inputs.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+pub struct SimulationStage;
+
+/// Phases of a simulation tick, in the order they run.
+///
+/// Every system of the [`SimulationStage`] belongs to one phase and carries its label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, SystemLabel)]
+pub enum SimulationSet {
+    /// Turns player and autoplayer decisions into changes to the game.
+    Input,
+    /// Brings in new targets and bullets, aimed at the targets alive at the start of the tick.
+    Spawning,
+    /// Moves targets and bullets by one tick.
+    Movement,
+    /// Finds the bullets that hit a target on their way this tick.
+    Collision,
+    /// Applies the damage of this tick's hits.
+    Damage,
+    /// Removes the targets that died or escaped this tick.
+    Death,
+    /// Settles the rewards and losses of this tick's deaths and drops what has expired, such as
+    /// bullets at the end of their lifetime.
+    Cleanup,
+}
+
+impl SimulationSet {
+    pub const ALL: [SimulationSet; 7] = [
+        SimulationSet::Input,
+        SimulationSet::Spawning,
+        SimulationSet::Movement,
+        SimulationSet::Collision,
+        SimulationSet::Damage,
+        SimulationSet::Death,
+        SimulationSet::Cleanup,
+    ];
+}
+
+/// Label of the exclusive system that ends every tick by storing the simulated positions.
+/// Exclusive systems that need the finished tick, with this tick's despawns applied, run after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct TickEnd;
+
+/// System marking where a phase starts, so systems can be ordered against phases nothing was
+/// added to yet. Boundary `SimulationSet::ALL.len()` marks the end of the tick.
+#[derive(Debug, Clone, Copy)]
+struct PhaseBoundary(usize);
+
+impl SystemLabel for PhaseBoundary {
+    fn as_str(&self) -> &'static str {
+        [
+            "start of input",
+            "start of spawning",
+            "start of movement",
+            "start of collision",
+            "start of damage",
+            "start of death",
+            "start of cleanup",
+            "end of tick",
+        ][self.0]
+    }
+}
+
+fn phase_boundary(mut timings: ResMut<SystemTimings>) {
+    timings.mark = Some(Instant::now());
+}
+
+fn start_tick(mut timings: ResMut<SystemTimings>) {
+    timings.ticks += 1;
+    timings.mark = Some(Instant::now());
+}
+
+/// Time spent in each gameplay system, measured by a probe that runs right after it.
+#[derive(Default, Debug)]
+pub struct SystemTimings {
+    mark: Option<Instant>,
+    /// Phase, name and accumulated time of each system, in registration order.
+    systems: Vec<(SimulationSet, &'static str, Duration)>,
+    ticks: u32,
+}
+
+impl SystemTimings {
+    fn register(&mut self, set: SimulationSet, name: &'static str) -> usize {
+        self.systems.push((set, name, Duration::ZERO));
+        self.systems.len() - 1
+    }
+
+    fn record(&mut self, index: usize) {
+        let now = Instant::now();
+        if let Some(mark) = self.mark.replace(now) {
+            self.systems[index].2 += now - mark;
+        }
+    }
+
+    /// Average time per tick of each system since the last call, in the order they run, along
+    /// with the number of ticks.
+    pub fn take(&mut self) -> (u32, Vec<(SimulationSet, &'static str, Duration)>) {
+        let ticks = std::mem::take(&mut self.ticks);
+        let mut averages: Vec<_> = self
+            .systems
+            .iter_mut()
+            .map(|(set, name, total)| (*set, *name, std::mem::take(total) / ticks.max(1)))
+            .collect();
+        averages.sort_by_key(|(set, _, _)| *set as usize);
+        (ticks, averages)
+    }
+}
+
+/// Position of a system in the simulation stage, in registration order.
+/// Labels need a `'static` name, so each step's name is leaked once when it is added.
+#[derive(Debug, Clone, Copy)]
+struct SimulationStep(&'static str);
+
+impl SystemLabel for SimulationStep {
+    fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+/// Timing probe of the last system added to each phase.
+#[derive(Default)]
+struct SimulationSteps {
+    count: usize,
+    last: HashMap<SimulationSet, SimulationStep>,
+}
+
+fn step_label(name: String) -> SimulationStep {
+    SimulationStep(Box::leak(name.into_boxed_str()))
+}
+
+pub trait SimulationAppExt {
+    /// Adds a gameplay system to `set` in the [`SimulationStage`], after every system of the
+    /// earlier phases and every system added to `set` before it.
+    ///
+    /// Bevy does not otherwise guarantee an order between unrelated systems,
+    /// not even in a single-threaded stage.
+    fn add_simulation_system<Params>(
+        &mut self,
+        set: SimulationSet,
+        system: impl ParallelSystemDescriptorCoercion<Params>,
+    ) -> &mut Self;
+}
+
+impl SimulationAppExt for App {
+    fn add_simulation_system<Params>(
+        &mut self,
+        set: SimulationSet,
+        system: impl ParallelSystemDescriptorCoercion<Params>,
+    ) -> &mut Self {
+        let name = std::any::type_name_of_val(&system);
+        let name = name.rsplit("::").next().unwrap_or(name);
+        let timing = self.world.resource_mut::<SystemTimings>().register(set, name);
+        let mut steps = self
+            .world
+            .get_resource_mut::<SimulationSteps>()
+            .expect("SimulationPlugin must be added before any simulation system");
+        let step = step_label(format!("simulation step {}", steps.count));
+        let probe = step_label(format!("simulation step {} timing", steps.count));
+        steps.count += 1;
+        let previous = steps.last.insert(set, probe);
+        let phase = set as usize;
+        let system = system
+            .label(step)
+            .label(set)
+            .after(PhaseBoundary(phase))
+            .before(PhaseBoundary(phase + 1));
+        let probe_system = (move |mut timings: ResMut<SystemTimings>| timings.record(timing))
+            .label(probe)
+            .after(step)
+            .before(PhaseBoundary(phase + 1));
+        match previous {
+            Some(previous) => self.add_system_to_stage(SimulationStage, system.after(previous)),
+            None => self.add_system_to_stage(SimulationStage, system),
+        }
+        .add_system_to_stage(SimulationStage, probe_system)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockMode {
+    /// Follows the frame time scaled by `SimulationClock::speed`.
+    RealTime,
+    /// Runs exactly this many ticks per frame, ignoring the frame time.
+    Stepped(u32),
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationClock {
+    pub mode: ClockMode,
+    pub speed: f64,
+    tick: u64,
+    accumulator: f64,
+    pending: u32,
+    looping: bool,
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        SimulationClock::new(ClockMode::RealTime)
+    }
+}
+
+impl SimulationClock {
+    pub fn new(mode: ClockMode) -> Self {
+        SimulationClock {
+            mode,
+            speed: 1.0,
+            tick: 0,
+            accumulator: 0.0,
+            pending: 0,
+            looping: false,
+        }
+    }
+
+    /// Length of one tick.
+    pub fn delta() -> Duration {
+        Duration::from_secs_f64(Self::delta_seconds() as f64)
+    }
+
+    pub fn delta_seconds() -> f32 {
+        1.0 / TICK_RATE as f32
+    }
+
+    /// Number of the tick being simulated, starting at 1 for the first tick.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Simulated time since the start of the game.
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.tick as f64 / TICK_RATE as f64
+    }
+
+    /// How far rendering is between the last tick and the next one, in `0.0..=1.0`.
+    pub fn overstep_fraction(&self) -> f32 {
+        match self.mode {
+            ClockMode::RealTime => (self.accumulator * TICK_RATE as f64).clamp(0.0, 1.0) as f32,
+            ClockMode::Stepped(_) => 1.0,
+        }
+    }
+
+    /// Continues the tick count of a restored game.
+    pub(crate) fn restore_tick(&mut self, tick: u64) {
+        self.tick = tick;
+        self.accumulator = 0.0;
+    }
+
+    fn begin_frame(&mut self, frame_delta: Duration) {
+        self.pending = match self.mode {
+            ClockMode::RealTime => {
+                let tick_seconds = 1.0 / TICK_RATE as f64;
+                self.accumulator += frame_delta.as_secs_f64() * self.speed.max(0.0);
+                let due = (self.accumulator / tick_seconds).floor() as u32;
+                let ticks = due.min(MAX_TICKS_PER_FRAME);
+                self.accumulator -= ticks as f64 * tick_seconds;
+                if due > ticks {
+                    self.accumulator = self.accumulator.min(tick_seconds);
+                }
+                ticks
+            }
+            ClockMode::Stepped(ticks) => ticks,
+        };
+    }
+}
+
+/// Marks an entity whose rendered translation is interpolated between the last two ticks.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl Interpolated {
+    pub fn new(translation: Vec3) -> Self {
+        Interpolated {
+            previous: translation,
+            current: translation,
+        }
+    }
+
+    /// Translation as of the last simulated tick, whatever is being rendered.
+    pub fn current(&self) -> Vec3 {
+        self.current
+    }
+}
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationClock>()
+            .init_resource::<SimulationSteps>()
+            .init_resource::<SystemTimings>()
+            .add_stage_after(
+                CoreStage::Update,
+                SimulationStage,
+                SystemStage::single_threaded().with_run_criteria(run_simulation),
+            )
+            .add_system_to_stage(
+                SimulationStage,
+                restore_simulated_transforms.exclusive_system().at_start(),
+            )
+            .add_system_to_stage(
+                SimulationStage,
+                record_simulated_transforms.exclusive_system().at_end().label(TickEnd),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_transforms.before(TransformSystem::TransformPropagate),
+            );
+        app.add_system_to_stage(SimulationStage, start_tick.label(PhaseBoundary(0)));
+        for boundary in 1..=SimulationSet::ALL.len() {
+            let system = phase_boundary
+                .label(PhaseBoundary(boundary))
+                .after(PhaseBoundary(boundary - 1));
+            app.add_system_to_stage(SimulationStage, system);
+        }
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn run_simulation(mut clock: ResMut<SimulationClock>, time: Res<Time>) -> ShouldRun {
+    if !clock.looping {
+        clock.begin_frame(time.delta());
+    }
+    if clock.pending > 0 {
+        clock.pending -= 1;
+        clock.tick += 1;
+        clock.looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        clock.looping = false;
+        ShouldRun::No
+    }
+}
+
+/// Puts interpolated entities back at their simulated position so gameplay systems
+/// never see the in-between positions used for rendering.
+fn restore_simulated_transforms(mut entities: Query<(&mut Interpolated, &mut Transform)>) {
+    for (mut interpolated, mut transform) in &mut entities {
+        transform.translation = interpolated.current;
+        interpolated.previous = interpolated.current;
+    }
+}
+
+fn record_simulated_transforms(mut entities: Query<(&mut Interpolated, &Transform)>) {
+    for (mut interpolated, transform) in &mut entities {
+        interpolated.current = transform.translation;
+    }
+}
+
+fn interpolate_transforms(
+    clock: Res<SimulationClock>,
+    mut entities: Query<(&Interpolated, &mut Transform)>,
+) {
+    let alpha = clock.overstep_fraction();
+    for (interpolated, mut transform) in &mut entities {
+        transform.translation = interpolated.previous.lerp(interpolated.current, alpha);
+    }
+}
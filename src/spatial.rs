@@ -0,0 +1,174 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::*;
+
+/// Side of a `TargetGrid` cell, a few times the usual target radius.
+pub const GRID_CELL_SIZE: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy)]
+struct GridEntry {
+    entity: Entity,
+    position: Vec3,
+    radius: f32,
+    /// Position of the target in query order, so lookups break ties the way a plain
+    /// loop over every target would.
+    order: usize,
+}
+
+/// Targets bucketed by position on the ground plane, so collision and targeting only look
+/// at nearby targets instead of all of them.
+///
+/// Rebuilt from the simulated transforms right before the systems that read it.
+#[derive(Debug, Default)]
+pub struct TargetGrid {
+    cells: HashMap<(i32, i32), Vec<GridEntry>>,
+    /// Smallest and largest occupied cell coordinates.
+    bounds: Option<((i32, i32), (i32, i32))>,
+    max_radius: f32,
+}
+
+impl TargetGrid {
+    fn cell(position: Vec3) -> (i32, i32) {
+        (
+            (position.x / GRID_CELL_SIZE).floor() as i32,
+            (position.z / GRID_CELL_SIZE).floor() as i32,
+        )
+    }
+
+    /// Replaces the contents with `targets`, given as entity, position and collider radius.
+    pub fn rebuild(&mut self, targets: impl IntoIterator<Item = (Entity, Vec3, f32)>) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.bounds = None;
+        self.max_radius = 0.0;
+        for (order, (entity, position, radius)) in targets.into_iter().enumerate() {
+            let cell = Self::cell(position);
+            self.cells.entry(cell).or_default().push(GridEntry {
+                entity,
+                position,
+                radius,
+                order,
+            });
+            self.max_radius = self.max_radius.max(radius);
+            self.bounds = Some(match self.bounds {
+                Some((min, max)) => (
+                    (min.0.min(cell.0), min.1.min(cell.1)),
+                    (max.0.max(cell.0), max.1.max(cell.1)),
+                ),
+                None => (cell, cell),
+            });
+        }
+    }
+
+    fn entries_in(&self, cell: (i32, i32)) -> &[GridEntry] {
+        self.cells.get(&cell).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The first target a point moving from `from` to `to` touches, the first in query order
+    /// when several are touched at once.
+    pub fn first_hit(&self, from: Vec3, to: Vec3) -> Option<(Entity, Vec3)> {
+        let reach = Vec3::splat(self.max_radius);
+        let (min_x, min_z) = Self::cell(from.min(to) - reach);
+        let (max_x, max_z) = Self::cell(from.max(to) + reach);
+        let mut first: Option<(FloatOrd, usize, GridEntry)> = None;
+        for x in min_x..=max_x {
+            for z in min_z..=max_z {
+                for entry in self.entries_in((x, z)) {
+                    let hit = match sweep_sphere(from, to, entry.position, entry.radius) {
+                        Some(hit) => (FloatOrd(hit), entry.order),
+                        None => continue,
+                    };
+                    if first.is_none_or(|(time, order, _)| hit < (time, order)) {
+                        first = Some((hit.0, hit.1, *entry));
+                    }
+                }
+            }
+        }
+        first.map(|(_, _, entry)| (entry.entity, entry.position))
+    }
+
+    /// The target nearest to `point`, the first in query order on ties.
+    ///
+    /// Searches rings of cells around `point`, skipping the rings that do not reach the occupied
+    /// cells and the parts of a ring outside them, so a far-away point costs no more than a near one.
+    pub fn nearest(&self, point: Vec3) -> Option<(Entity, Vec3)> {
+        let (min, max) = self.bounds?;
+        let center = Self::cell(point);
+        let gap = |at: i32, low: i32, high: i32| (low - at).max(at - high).max(0);
+        let first_ring = gap(center.0, min.0, max.0).max(gap(center.1, min.1, max.1));
+        let mut best: Option<(FloatOrd, usize, GridEntry)> = None;
+        for ring in first_ring.. {
+            // Cells from this ring outwards are at least `ring - 1` cells away from `point`.
+            if let Some((FloatOrd(distance), _, _)) = best {
+                if distance < (ring - 1) as f32 * GRID_CELL_SIZE {
+                    break;
+                }
+            }
+            let ring_min = (center.0 - ring, center.1 - ring);
+            let ring_max = (center.0 + ring, center.1 + ring);
+            if ring_min.0 < min.0 && ring_min.1 < min.1 && ring_max.0 > max.0 && ring_max.1 > max.1 {
+                break;
+            }
+            let (low, high) = (ring_min.1.max(min.1), ring_max.1.min(max.1));
+            for x in ring_min.0.max(min.0)..=ring_max.0.min(max.0) {
+                // Columns on the sides of the ring lie on it entirely, the others only at both ends.
+                let on_side = x == ring_min.0 || x == ring_max.0;
+                let (from, to, step) = if on_side {
+                    (low, high, 1)
+                } else {
+                    (ring_min.1, ring_max.1, 2 * ring as usize)
+                };
+                for z in (from..=to).step_by(step).filter(|z| (low..=high).contains(z)) {
+                    for entry in self.entries_in((x, z)) {
+                        let key = (FloatOrd(Vec3::distance(entry.position, point)), entry.order);
+                        if best.is_none_or(|(distance, order, _)| key < (distance, order)) {
+                            best = Some((key.0, key.1, *entry));
+                        }
+                    }
+                }
+            }
+        }
+        best.map(|(_, _, entry)| (entry.entity, entry.position))
+    }
+}
+
+/// How far along the segment from `from` to `to`, between 0 and 1, it first comes closer than
+/// `radius` to `center`.
+pub fn sweep_sphere(from: Vec3, to: Vec3, center: Vec3, radius: f32) -> Option<f32> {
+    let offset = from - center;
+    if offset.length() < radius {
+        return Some(0.0);
+    }
+    let step = to - from;
+    let a = step.length_squared();
+    let b = offset.dot(step);
+    let discriminant = b * b - a * (offset.length_squared() - radius * radius);
+    if a == 0.0 || discriminant <= 0.0 {
+        return None;
+    }
+    let time = (-b - discriminant.sqrt()) / a;
+    (0.0..=1.0).contains(&time).then_some(time)
+}
+
+pub struct SpatialPlugin;
+
+impl Plugin for SpatialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TargetGrid>();
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Refreshes the `TargetGrid`. Added as a simulation step before each system that reads it,
+/// since targets move in between.
+pub fn index_targets(mut grid: ResMut<TargetGrid>, targets: Query<(Entity, &Transform, &Target)>) {
+    grid.rebuild(
+        targets
+            .iter()
+            .map(|(entity, transform, target)| (entity, transform.translation, target.radius)),
+    );
+}
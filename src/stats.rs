@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Combat counters of a single tower, used to balance tower types.
+#[derive(Reflect, Component, Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct TowerStats {
+    pub shots_fired: u32,
+    pub hits: u32,
+    pub damage: u32,
+    /// Ticks the tower has existed for.
+    pub active_ticks: u64,
+}
+
+impl TowerStats {
+    /// Damage per second since the tower was built.
+    pub fn dps(&self) -> f64 {
+        if self.active_ticks == 0 {
+            return 0.0;
+        }
+        self.damage as f64 * TICK_RATE as f64 / self.active_ticks as f64
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WaveStats {
+    pub spawned: u32,
+    pub killed: u32,
+    pub leaked: u32,
+}
+
+/// Outcome of each wave of the current game, indexed like `Level::waves`.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct GameStats {
+    pub waves: Vec<WaveStats>,
+}
+
+impl GameStats {
+    pub fn wave_mut(&mut self, index: usize) -> &mut WaveStats {
+        if self.waves.len() <= index {
+            self.waves.resize(index + 1, WaveStats::default());
+        }
+        &mut self.waves[index]
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<TowerStats>()
+            .init_resource::<GameStats>()
+            .add_simulation_system(SimulationSet::Damage, record_hits)
+            .add_simulation_system(SimulationSet::Cleanup, record_wave_outcomes);
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn record_hits(mut hits: EventReader<DamageEvent>, mut towers: Query<&mut TowerStats>) {
+    for hit in hits.iter() {
+        if let Some(mut stats) = hit.source_tower.and_then(|tower| towers.get_mut(tower).ok()) {
+            stats.hits += 1;
+            stats.damage += hit.amount.max(0) as u32;
+        }
+    }
+}
+
+fn record_wave_outcomes(
+    mut killed: EventReader<TargetKilled>,
+    mut leaked: EventReader<TargetLeaked>,
+    mut stats: ResMut<GameStats>,
+) {
+    for event in killed.iter() {
+        stats.wave_mut(event.target.wave).killed += 1;
+    }
+    for event in leaked.iter() {
+        stats.wave_mut(event.target.wave).leaked += 1;
+    }
+}
@@ -0,0 +1,201 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::*;
+
+/// Distance between two build slots along the stress path.
+const STRESS_SLOT_SPACING: f32 = 4.0;
+
+/// A long straight path lined on both sides with towers of every type, and one wave of many
+/// targets, simulated without a window to find out how the game scales.
+#[derive(Debug, Clone)]
+pub struct StressTest {
+    pub targets: u32,
+    /// Length of the path, which sets the number of build slots.
+    pub path_length: f32,
+    /// Simulated seconds to run for.
+    pub seconds: f64,
+    pub seed: u64,
+}
+
+impl Default for StressTest {
+    fn default() -> Self {
+        StressTest {
+            targets: 2000,
+            path_length: 200.0,
+            seconds: 60.0,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SystemReport {
+    pub phase: String,
+    pub system: String,
+    /// Average time per tick, in microseconds.
+    pub micros: f64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StressReport {
+    pub targets: u32,
+    pub towers: usize,
+    pub ticks: u64,
+    /// Wall-clock seconds the run took.
+    pub seconds: f64,
+    /// Frame times in milliseconds, one frame per tick.
+    pub average_frame: f64,
+    pub frame_p99: f64,
+    pub worst_frame: f64,
+    pub peak_targets: usize,
+    pub peak_bullets: usize,
+    /// Entities left in the world at the end, including pooled bullets.
+    pub entities: u32,
+    pub systems: Vec<SystemReport>,
+}
+
+impl StressTest {
+    /// Slots every few units on both sides of the path, and a single wave queued behind its start.
+    pub fn level(&self) -> Level {
+        let path_start = Vec3::new(-4.0, 0.4, 2.5);
+        let slots_per_side = (self.path_length / STRESS_SLOT_SPACING).floor() as usize + 1;
+        let slots = (0..slots_per_side)
+            .flat_map(|i| {
+                let x = STRESS_SLOT_SPACING * i as f32;
+                [Vec3::new(x, 0.8, 0.0), Vec3::new(x, 0.8, 5.0)]
+            })
+            .collect();
+        Level {
+            name: "stress".to_string(),
+            slots,
+            path_start,
+            path_end: self.path_length + 4.0,
+            waves: vec![Wave {
+                count: self.targets,
+                spacing: 0.5,
+                health: 20,
+                ..Wave::default()
+            }],
+        }
+    }
+
+    /// Fills every slot, cycling through the tower types.
+    pub fn plan(&self, level: &Level) -> PlacementPlan {
+        PlacementPlan {
+            towers: (0..level.slots.len())
+                .map(|slot| PlannedTower {
+                    slot,
+                    tower_type: TowerType::ALL[slot % TowerType::ALL.len()],
+                })
+                .collect(),
+        }
+    }
+
+    /// Runs the stress test, one tick per frame as fast as possible, and measures each frame.
+    pub fn run(&self) -> StressReport {
+        let level = self.level();
+        let plan = self.plan(&level);
+        let mut app = App::new();
+        app.insert_resource(level)
+            .insert_resource(GameRng::new(self.seed))
+            .insert_resource(AutoPlayer::new(PlanStrategy::new(&plan)))
+            .add_plugin(HeadlessPlugin)
+            .add_startup_system(spawn_headless_scene)
+            .add_startup_system(|mut player: ResMut<Player>| player.gold = u32::MAX / 2);
+
+        let ticks = (self.seconds * TICK_RATE as f64).ceil() as u64;
+        let mut frames = Vec::with_capacity(ticks as usize);
+        let mut systems = SystemTotals::default();
+        let (mut peak_targets, mut peak_bullets) = (0, 0);
+        let started = Instant::now();
+        for _ in 0..ticks {
+            let frame = Instant::now();
+            app.update();
+            frames.push(frame.elapsed());
+
+            systems.add_report(app.world.resource::<PerformanceReport>());
+            peak_targets = peak_targets.max(count::<With<Target>>(&mut app.world));
+            peak_bullets = peak_bullets.max(count::<With<Bullet>>(&mut app.world));
+        }
+        let seconds = started.elapsed().as_secs_f64();
+        systems.add_timings(app.world.resource_mut::<SystemTimings>().take());
+
+        let summary = PerformanceReport::default().with_frames(&frames, seconds);
+        let total: Duration = frames.iter().sum();
+        StressReport {
+            targets: self.targets,
+            towers: count::<With<Tower>>(&mut app.world),
+            ticks,
+            seconds,
+            average_frame: total.as_secs_f64() * 1000.0 / ticks.max(1) as f64,
+            frame_p99: summary.frame_p99,
+            worst_frame: summary.frame_max,
+            peak_targets,
+            peak_bullets,
+            entities: app.world.entities().len(),
+            systems: systems.report(),
+        }
+    }
+}
+
+fn count<F: bevy::ecs::query::WorldQuery>(world: &mut World) -> usize {
+    world.query_filtered::<(), F>().iter(world).count()
+}
+
+/// Time each gameplay system took over the whole run, gathered from the performance reports
+/// made along the way and the timings since the last of them.
+#[derive(Default)]
+struct SystemTotals {
+    last_report: f64,
+    ticks: u64,
+    /// Phase, name and total microseconds of each system, in the order they run.
+    totals: Vec<(SimulationSet, &'static str, f64)>,
+}
+
+impl SystemTotals {
+    fn add_report(&mut self, report: &PerformanceReport) {
+        if report.time == self.last_report {
+            return;
+        }
+        self.last_report = report.time;
+        let systems = report.systems.iter().map(|(set, name, micros)| (*set, *name, *micros));
+        self.add(report.ticks, systems);
+    }
+
+    fn add_timings(&mut self, (ticks, systems): (u32, Vec<(SimulationSet, &'static str, Duration)>)) {
+        let systems = systems
+            .into_iter()
+            .map(|(set, name, time)| (set, name, time.as_secs_f64() * 1e6));
+        self.add(ticks, systems);
+    }
+
+    fn add(&mut self, ticks: u32, averages: impl Iterator<Item = (SimulationSet, &'static str, f64)>) {
+        self.ticks += ticks as u64;
+        for (i, (set, name, micros)) in averages.enumerate() {
+            if i == self.totals.len() {
+                self.totals.push((set, name, 0.0));
+            }
+            self.totals[i].2 += micros * ticks as f64;
+        }
+    }
+
+    fn report(&self) -> Vec<SystemReport> {
+        self.totals
+            .iter()
+            .map(|(set, name, total)| SystemReport {
+                phase: format!("{:?}", set),
+                system: name.to_string(),
+                micros: total / self.ticks.max(1) as f64,
+            })
+            .collect()
+    }
+}
+
+impl StressReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("stress reports always serialize")
+    }
+}
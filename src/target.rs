@@ -1,38 +1,125 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Reflect, Component, Default)]
+use crate::*;
+
+pub const TARGET_HEALTH: i32 = 5;
+pub const TARGET_SPEED: f32 = 0.6;
+pub const TARGET_BOUNTY: u32 = 5;
+/// Radius of the sphere bullets have to touch to hit a target.
+pub const TARGET_RADIUS: f32 = 0.4;
+
+pub(crate) fn default_target_radius() -> f32 {
+    TARGET_RADIUS
+}
+
+#[derive(Reflect, Component, Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[reflect(Component)]
 pub struct Target {
     pub speed: f32,
+    pub bounty: u32,
+    /// Index of the wave the target belongs to.
+    pub wave: usize,
+    #[serde(default = "default_target_radius")]
+    pub radius: f32,
 }
 
-#[derive(Reflect, Component, Default)]
+#[derive(Reflect, Component, Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[reflect(Component)]
 pub struct Health {
     pub value: i32,
 }
 
+/// Sent in the death phase for each target whose health ran out, right before it is despawned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TargetKilled {
+    pub entity: Entity,
+    pub target: Target,
+    pub position: Vec3,
+}
+
+/// Sent in the death phase for each target that walked past the end of the path, right before
+/// it is despawned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TargetLeaked {
+    pub entity: Entity,
+    pub target: Target,
+    pub position: Vec3,
+}
+
 pub struct TargetPlugin;
 
 impl Plugin for TargetPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Target>()
             .register_type::<Health>()
-            .add_system(move_targets)
-            .add_system(target_death);
+            .add_event::<TargetKilled>()
+            .add_event::<TargetLeaked>()
+            .add_simulation_system(SimulationSet::Movement, move_targets)
+            .add_simulation_system(SimulationSet::Death, target_death)
+            .add_simulation_system(SimulationSet::Death, target_leak);
     }
 }
 
-fn target_death(mut commands: Commands, targets: Query<(Entity, &Health)>) {
-    for (ent, health) in &targets {
+pub fn spawn_target(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    position: Vec3,
+    target: Target,
+    health: Health,
+) -> Entity {
+    commands
+        .spawn_bundle(SceneBundle {
+            scene: assets.target_scene.clone(),
+            transform: Transform::from_translation(position),
+            ..Default::default()
+        })
+        .insert(target)
+        .insert(health)
+        .insert(Interpolated::new(position))
+        .insert(Name::new("Target"))
+        .id()
+}
+
+fn target_death(
+    mut commands: Commands,
+    targets: Query<(Entity, &Health, &Target, &Transform)>,
+    mut killed: EventWriter<TargetKilled>,
+) {
+    for (ent, health, target, transform) in &targets {
         if health.value <= 0 {
             commands.entity(ent).despawn_recursive();
+            killed.send(TargetKilled {
+                entity: ent,
+                target: *target,
+                position: transform.translation,
+            });
+        }
+    }
+}
+
+/// Despawns targets past the end of the path, unless they died on this tick: those count as
+/// killed, not leaked.
+fn target_leak(
+    mut commands: Commands,
+    targets: Query<(Entity, &Transform, &Target, &Health)>,
+    level: Res<Level>,
+    mut leaked: EventWriter<TargetLeaked>,
+) {
+    for (ent, transform, target, health) in &targets {
+        if transform.translation.x > level.path_end && health.value > 0 {
+            commands.entity(ent).despawn_recursive();
+            leaked.send(TargetLeaked {
+                entity: ent,
+                target: *target,
+                position: transform.translation,
+            });
         }
     }
 }
 
-fn move_targets(mut targets: Query<(&Target, &mut Transform)>, time: Res<Time>) {
+pub fn move_targets(mut targets: Query<(&Target, &mut Transform)>) {
     for (target, mut transform) in &mut targets {
-        transform.translation.x += target.speed * time.delta_seconds();
+        transform.translation.x += target.speed * SimulationClock::delta_seconds();
     }
 }
@@ -1,4 +1,5 @@
 pub mod shooter;
 pub mod tower_type;
+#[allow(clippy::module_inception)]
 pub mod tower;
 pub mod ui;
\ No newline at end of file
@@ -8,10 +8,10 @@ use super::tower_type::*;
 pub struct TowerShooter<'a> {
     entity: Entity, 
     tower_type: &'a TowerType, 
-    transform: &'a GlobalTransform
+    transform: &'a Transform
 }
 impl<'a> TowerShooter<'a> {
-    pub fn new(entity: Entity, tower_type: &'a TowerType, transform: &'a GlobalTransform) -> Self {
+    pub fn new(entity: Entity, tower_type: &'a TowerType, transform: &'a Transform) -> Self {
         TowerShooter {
             entity,
             tower_type,
@@ -20,59 +20,81 @@ impl<'a> TowerShooter<'a> {
     }
 
     fn get_bullet_spawn(&self, tower: &Tower) -> Vec3 {
-        self.transform.translation() + tower.bullet_offset
+        self.transform.translation + tower.bullet_offset
     }        
 
-    fn distance_from(&self, target: Vec3, bullet_spawn: Vec3) -> FloatOrd {
-        FloatOrd(Vec3::distance(target, bullet_spawn))
-    }
-    
-    fn get_direction(&self, tower: &Tower, targets: &Query<&GlobalTransform, With<Target>>) -> Option<Vec3> {
+    /// Direction from the bullet spawn point to the nearest target, if there is one.
+    pub fn get_direction(&self, tower: &Tower, targets: &TargetGrid) -> Option<Vec3> {
         let bullet_spawn: Vec3 = self.get_bullet_spawn(tower);
         targets
-            .iter()
-            .min_by_key(|target_transform| {
-                // FloatOrd(Vec3::distance(target_transform.translation(), bullet_spawn))
-                self.distance_from(target_transform.translation(), bullet_spawn)
-            })
-            .map(|closest_target| closest_target.translation() - bullet_spawn)
+            .nearest(bullet_spawn)
+            .map(|(_, closest_target)| closest_target - bullet_spawn)
     }
-    
-    pub fn shoot_from(&self, commands: &mut Commands, tower: &Tower, targets: &Query<&GlobalTransform, With<Target>>, bullet_assets: &GameAssets) {
-        let ctx = (commands, tower);
-        if let Some(direction) = self.get_direction(tower, targets) {    
-            self.shoot_direction(ctx, direction, bullet_assets)
+
+    /// Shoots at the nearest target, returning whether there was one.
+    pub fn shoot_from(&self, commands: &mut Commands, pool: &mut ProjectilePool, tower: &Tower, targets: &TargetGrid, bullet_assets: &GameAssets) -> bool {
+        let ctx = (commands, pool, tower);
+        match self.get_direction(tower, targets) {
+            Some(direction) => {
+                self.shoot_direction(ctx, direction, bullet_assets);
+                true
+            }
+            None => false,
         }
-        else { return };
     }
 
-    fn shoot_direction(&self, ctx: (&mut Commands,  &Tower), direction: Vec3, bullet_assets: &GameAssets) {
-        let (model, bullet) = self.tower_type.get_bullet(direction, &bullet_assets);
+    fn shoot_direction(&self, ctx: (&mut Commands, &mut ProjectilePool, &Tower), direction: Vec3, bullet_assets: &GameAssets) {
+        let (model, bullet) = self.tower_type.get_bullet(direction, bullet_assets);
         self.spawn(ctx, model, bullet)
     }
 
-    fn bullet_lifetime(&self) -> Lifetime {
-        Lifetime {
-            timer: Timer::from_seconds(10.0, false),
-        }        
+    fn spawn(&self, ctx: (&mut Commands, &mut ProjectilePool, &Tower), scene: Handle<Scene>, mut bullet: Bullet) {
+        let (commands, pool, tower) = ctx;
+        bullet.source = Some(self.entity);
+        spawn_bullet(commands, pool, *self.tower_type, scene, self.get_bullet_spawn(tower), bullet);
     }
+}
 
-    fn scene_bundle(&self, tower: &Tower, scene: Handle<Scene>) -> SceneBundle {
-        SceneBundle {
-            scene,
-            transform: Transform::from_translation(tower.bullet_offset),
-            ..Default::default()
-        }        
+pub fn bullet_lifetime() -> Lifetime {
+    Lifetime {
+        timer: Timer::from_seconds(10.0, false),
     }
+}
 
-    fn spawn(&self, ctx: (&mut Commands,  &Tower), scene: Handle<Scene>, bullet: Bullet) {
-        let (commands, tower) = ctx;
-        commands.entity(self.entity).with_children(|commands| {
-            commands
-                .spawn_bundle(self.scene_bundle(&tower, scene))
-                .insert(self.bullet_lifetime())
-                .insert(bullet)
-                .insert(Name::new("Bullet"));
-        });        
+/// Fires a bullet of `tower_type` at world position `translation`, with a full `bullet_lifetime`.
+///
+/// Bullets are root entities, so they keep flying if the tower in their `source` goes away.
+/// An idle bullet from the pool is reused when there is one; `scene` is only instantiated otherwise.
+pub fn spawn_bullet(
+    commands: &mut Commands,
+    pool: &mut ProjectilePool,
+    tower_type: TowerType,
+    scene: Handle<Scene>,
+    translation: Vec3,
+    bullet: Bullet,
+) -> Entity {
+    let lifetime = bullet_lifetime();
+    if let Some(entity) = pool.acquire(tower_type) {
+        commands
+            .entity(entity)
+            .insert(Transform::from_translation(translation))
+            .insert(Interpolated::new(translation))
+            .insert(Visibility::default())
+            .insert(lifetime)
+            .insert(bullet);
+        return entity;
     }
+    pool.record_created();
+    commands
+        .spawn_bundle(SceneBundle {
+            scene,
+            transform: Transform::from_translation(translation),
+            ..Default::default()
+        })
+        .insert(lifetime)
+        .insert(Interpolated::new(translation))
+        .insert(bullet)
+        .insert(PooledBullet(tower_type))
+        .insert(Name::new("Bullet"))
+        .id()
 }
\ No newline at end of file
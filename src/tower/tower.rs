@@ -1,7 +1,6 @@
 use bevy::prelude::*;
 
 use crate::*;
-use super::ui::*;
 use super::shooter::*;
 use super::tower_type::*;
 
@@ -18,9 +17,8 @@ pub struct TowerPlugin;
 impl Plugin for TowerPlugin {
     fn build<'a>(&self, app: &mut App) {
         app.register_type::<Tower>()
-            .add_system(tower_shooting)
-            .add_system(tower_button_clicked)
-            .add_system(create_ui_on_selection);
+            .add_simulation_system(SimulationSet::Spawning, index_targets)
+            .add_simulation_system(SimulationSet::Spawning, tower_shooting);
     }
 
     fn name(&self) -> &str {
@@ -31,16 +29,19 @@ impl Plugin for TowerPlugin {
 
 fn tower_shooting(
     mut commands: Commands,
-    mut towers: Query<(Entity, &mut Tower, &TowerType, &GlobalTransform)>,
-    targets: Query<&GlobalTransform, With<Target>>,
+    mut towers: Query<(Entity, &mut Tower, &TowerType, &Transform, &mut TowerStats)>,
+    targets: Res<TargetGrid>,
+    mut pool: ResMut<ProjectilePool>,
     bullet_assets: Res<GameAssets>,
-    time: Res<Time>,
 ) {
-    for (entity, mut tower, tower_type, transform) in &mut towers {
-        let tower_shooter = TowerShooter::new(entity, &tower_type, &transform);
-        tower.shooting_timer.tick(time.delta());
-        if tower.shooting_timer.just_finished() {
-            tower_shooter.shoot_from(&mut commands, &tower, &targets, &bullet_assets)
+    for (entity, mut tower, tower_type, transform, mut stats) in &mut towers {
+        let tower_shooter = TowerShooter::new(entity, tower_type, transform);
+        tower.shooting_timer.tick(SimulationClock::delta());
+        stats.active_ticks += 1;
+        if tower.shooting_timer.just_finished()
+            && tower_shooter.shoot_from(&mut commands, &mut pool, &tower, &targets, &bullet_assets)
+        {
+            stats.shots_fired += 1;
         }
     }
 }
@@ -56,9 +57,11 @@ pub fn spawn_tower(
         .spawn_bundle(SpatialBundle::from_transform(Transform::from_translation(
             position,
         )))
-        .insert(Name::new(format!("{:?}_Tower", tower_type)))
+        .insert(Name::new(tower_type.name_key()))
+        .insert(Localized::new(tower_type.name_key()))
         .insert(tower_type)
         .insert(tower)
+        .insert(TowerStats::default())
         .with_children(|commands| {
             commands.spawn_bundle(SceneBundle {
                 scene: tower_scene,
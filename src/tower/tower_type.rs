@@ -1,10 +1,11 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::*;
 use super::tower::*;
 
 
-#[derive(Inspectable, Component, Clone, Copy, Debug)]
+#[derive(Inspectable, Component, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum TowerType {
     Tomato,
     Potato,
@@ -12,13 +13,33 @@ pub enum TowerType {
 }
 
 impl TowerType {
+    pub const ALL: [TowerType; 3] = [TowerType::Tomato, TowerType::Potato, TowerType::Cabbage];
+
+    /// Gold it takes to build the tower.
+    pub fn cost(&self) -> u32 {
+        match self {
+            TowerType::Tomato => 30,
+            TowerType::Potato => 40,
+            TowerType::Cabbage => 50,
+        }
+    }
+
+    /// Seconds between two shots.
+    pub fn shot_interval(&self) -> f32 {
+        match self {
+            TowerType::Tomato => 1.0,
+            TowerType::Potato => 2.0,
+            TowerType::Cabbage => 3.0,
+        }
+    }
+
     fn timer(&self, duration: f32) -> Timer {
         Timer::from_seconds(duration, true)
     }
 
-    fn create_tower(&self, duration: f32) -> Tower {     
+    fn create_tower(&self) -> Tower {     
         Tower {
-            shooting_timer: self.timer(duration),
+            shooting_timer: self.timer(self.shot_interval()),
             bullet_offset: self.offset(),
         }
     }
@@ -27,6 +48,17 @@ impl TowerType {
         Bullet {
             direction,
             speed,
+            travelled: Vec3::ZERO,
+            source: None,
+        }
+    }
+
+    /// String table key of the player-facing tower name.
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            TowerType::Tomato => "tower-tomato",
+            TowerType::Potato => "tower-potato",
+            TowerType::Cabbage => "tower-cabbage",
         }
     }
 
@@ -39,15 +71,15 @@ impl TowerType {
         match self {
             TowerType::Tomato => (
                 assets.tomato_tower_scene.clone(),
-                self.create_tower(1.0)
+                self.create_tower()
             ),
             TowerType::Potato => (
                 assets.potato_tower_scene.clone(),
-                self.create_tower( 2.0)
+                self.create_tower()
             ),
             TowerType::Cabbage => (
                 assets.cabbage_tower_scene.clone(),
-                self.create_tower( 3.0)
+                self.create_tower()
             ),
         }
     }
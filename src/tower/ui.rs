@@ -1,89 +1,353 @@
-use bevy::{prelude::*, ecs::query::QuerySingleError};
+use bevy::{ecs::system::EntityCommands, prelude::*, window::WindowResized};
 use crate::*;
 
-use super::tower_type::*;
 use super::tower::*;
+use super::tower_type::*;
+
+/// Which menu is shown for the selected build slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotMenu {
+    /// Buttons to build each tower type on an empty slot.
+    Build,
+    /// Name and damage of the tower built on the slot.
+    Tower,
+}
+
+/// Root node of the menu opened for a selected build slot.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TowerUIRoot {
+    /// Selected slot or tower the menu was opened for.
+    pub selected: Entity,
+    pub menu: SlotMenu,
+}
+
+#[derive(Component)]
+pub struct TowerButton;
+
+#[derive(Component)]
+pub struct TowerButtonLabel;
 
+/// Picture of the tower in the tower menu, sized like a build button.
 #[derive(Component)]
-pub struct TowerUIRoot;
+pub struct TowerMenuImage;
+
+/// Text of the tower menu, its first section localized to the tower's name.
+#[derive(Component)]
+pub struct TowerInfoLabel;
+
+const TOWER_BUTTONS: usize = 3;
+
+/// The build menu and its hotkeys, only used when the game runs with a window.
+pub struct TowerUiPlugin;
+
+impl Plugin for TowerUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(tower_button_clicked)
+            .add_system(tower_hotkeys)
+            .add_system(resize_ui)
+            .add_system(update_menu_on_selection)
+            .add_system(update_tower_info.after(update_menu_on_selection));
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Pixel sizes of the build menu for the current window.
+///
+/// Buttons stay square and scale with the shorter side of the window, shrinking further
+/// when the whole row would not fit horizontally (e.g. on portrait monitors).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiLayout {
+    pub button_size: f32,
+    pub margin: f32,
+    pub font_size: f32,
+}
+
+impl UiLayout {
+    pub fn new(width: f32, height: f32, ui_scale: f32) -> Self {
+        let row_width = TOWER_BUTTONS as f32 * 1.3;
+        let button_size = (height * 0.15 * ui_scale).min(width / row_width);
+        UiLayout {
+            button_size,
+            margin: button_size * 0.15,
+            font_size: button_size / 6.0,
+        }
+    }
+
+    pub fn from_windows(windows: &Windows, ui_scale: f32) -> Self {
+        match windows.get_primary() {
+            Some(window) => UiLayout::new(window.width(), window.height(), ui_scale),
+            None => UiLayout::new(1280.0, 720.0, ui_scale),
+        }
+    }
+
+    fn apply(&self, style: &mut Style) {
+        style.size = Size::new(Val::Px(self.button_size), Val::Px(self.button_size));
+        style.margin = UiRect::all(Val::Px(self.margin));
+    }
+}
+
+/// Build slots without a tower that can be selected.
+type FreeSlots<'w, 's> = Query<'w, 's, (&'static Selection, &'static BuildSlot), Without<Tower>>;
 
 pub fn tower_button_clicked(
     interaction: Query<(&Interaction, &TowerType), Changed<Interaction>>,
-    mut commands: Commands,
-    selection: Query<(Entity, &Selection, &Transform)>,
-    assets: Res<GameAssets>,
+    mut actions: ResMut<ActionQueue>,
+    selection: FreeSlots,
 ) {
     for (interaction, tower_type) in &interaction {
         if matches!(interaction, Interaction::Clicked) {
-            for (entity, selection, transform) in &selection {
-                if selection.selected() {
-                    //Remove the base model/hitbox
-                    commands.entity(entity).despawn_recursive();
-
-                    spawn_tower(&mut commands, &assets, transform.translation, *tower_type);
-                }
-            }
+            build_on_selection(&mut actions, &selection, *tower_type);
         }
     }
 }
 
-pub fn create_ui(commands: &mut Commands, asset_server: &AssetServer) {
-    let button_icons = [
-        asset_server.load("tomato_tower.png"),
-        asset_server.load("potato_tower.png"),
-        asset_server.load("cabbage_tower.png"),
+pub fn tower_hotkeys(
+    input: ActionInput,
+    mut actions: ResMut<ActionQueue>,
+    selection: FreeSlots,
+) {
+    let hotkeys = [
+        (Action::BuildTomato, TowerType::Tomato),
+        (Action::BuildPotato, TowerType::Potato),
+        (Action::BuildCabbage, TowerType::Cabbage),
     ];
+    for (action, tower_type) in hotkeys {
+        if input.just_pressed(action) {
+            build_on_selection(&mut actions, &selection, tower_type);
+        }
+    }
+}
 
-    let towers = [TowerType::Tomato, TowerType::Potato, TowerType::Cabbage];
-    commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                justify_content: JustifyContent::Center,
-                ..default()
-            },
-            color: Color::NONE.into(),
+fn build_on_selection(actions: &mut ActionQueue, selection: &FreeSlots, tower_type: TowerType) {
+    for (selection, slot) in selection {
+        if selection.selected() {
+            actions.push(PlayerAction::BuildTower {
+                slot: slot.0,
+                tower_type,
+            });
+        }
+    }
+}
+
+fn tower_icon(tower_type: TowerType) -> &'static str {
+    match tower_type {
+        TowerType::Tomato => "tomato_tower.png",
+        TowerType::Potato => "potato_tower.png",
+        TowerType::Cabbage => "cabbage_tower.png",
+    }
+}
+
+fn spawn_menu_root<'w, 's, 'a>(commands: &'a mut Commands<'w, 's>, root: TowerUIRoot) -> EntityCommands<'w, 's, 'a> {
+    let mut menu = commands.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            justify_content: JustifyContent::Center,
             ..default()
-        })
-        .insert(TowerUIRoot)
+        },
+        color: Color::NONE.into(),
+        ..default()
+    });
+    menu.insert(root);
+    menu
+}
+
+/// Spawns the build menu of the empty slot `selected`.
+pub fn create_ui(commands: &mut Commands, asset_server: &AssetServer, layout: &UiLayout, selected: Entity) {
+    let button_icons = TowerType::ALL.map(|tower_type| asset_server.load(tower_icon(tower_type)));
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    let towers = TowerType::ALL;
+    let root = TowerUIRoot {
+        selected,
+        menu: SlotMenu::Build,
+    };
+    spawn_menu_root(commands, root)
         .with_children(|commands| {
-            for i in 0..3 {
+            for i in 0..TOWER_BUTTONS {
                 commands
                     .spawn_bundle(ButtonBundle {
-                        style: Style {
-                            size: Size::new(Val::Percent(15.0 * 9.0 / 16.0), Val::Percent(15.0)),
-                            align_self: AlignSelf::FlexStart,
-                            margin: UiRect::all(Val::Percent(2.0)),
-                            ..default()
+                        style: {
+                            let mut style = Style {
+                                align_self: AlignSelf::FlexStart,
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::FlexStart,
+                                ..default()
+                            };
+                            layout.apply(&mut style);
+                            style
                         },
                         image: button_icons[i].clone().into(),
                         ..default()
                     })
-                    .insert(towers[i]);
+                    .insert(towers[i])
+                    .insert(TowerButton)
+                    .with_children(|commands| {
+                        commands
+                            .spawn_bundle(TextBundle::from_section(
+                                towers[i].name_key(),
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: layout.font_size,
+                                    color: Color::WHITE,
+                                },
+                            ))
+                            .insert(Localized::new(towers[i].name_key()))
+                            .insert(TowerButtonLabel);
+                    });
             }
         });
 }
 
-pub fn create_ui_on_selection(
+/// Spawns the menu of the selected `tower`, showing its name and damage per second.
+pub fn create_tower_menu(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    layout: &UiLayout,
+    tower: Entity,
+    tower_type: TowerType,
+) {
+    let root = TowerUIRoot {
+        selected: tower,
+        menu: SlotMenu::Tower,
+    };
+    let style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: layout.font_size,
+        color: Color::WHITE,
+    };
+    spawn_menu_root(commands, root).with_children(|commands| {
+        commands
+            .spawn_bundle(ImageBundle {
+                style: {
+                    let mut style = Style {
+                        align_self: AlignSelf::FlexStart,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::FlexStart,
+                        ..default()
+                    };
+                    layout.apply(&mut style);
+                    style
+                },
+                image: asset_server.load(tower_icon(tower_type)).into(),
+                ..default()
+            })
+            .insert(TowerMenuImage)
+            .with_children(|commands| {
+                commands
+                    .spawn_bundle(TextBundle::from_sections([
+                        TextSection::new(tower_type.name_key(), style.clone()),
+                        TextSection::new("", style),
+                    ]))
+                    .insert(Localized::new(tower_type.name_key()))
+                    .insert(TowerInfoLabel);
+            });
+    });
+}
+
+/// Opens the menu of the selected slot when the selection changes, and closes it once the slot
+/// is no longer selected or gone, e.g. because a tower replaced it.
+///
+/// Only one menu is ever kept: any extra root is despawned.
+pub fn update_menu_on_selection(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    //Perf could probably be smarter with change detection
-    selections: Query<&Selection>,
-    root: Query<Entity, With<TowerUIRoot>>,
+    (asset_server, settings, windows): (Res<AssetServer>, Res<Settings>, Res<Windows>),
+    changed: Query<(), Changed<Selection>>,
+    selections: Query<(Entity, &Selection, Option<&TowerType>), With<BuildSlot>>,
+    roots: Query<(Entity, &TowerUIRoot)>,
 ) {
-    let at_least_one_selected = selections.iter().any(|selection| selection.selected());
-    match root.get_single() {
-        Ok(root) => {
-            if !at_least_one_selected {
-                commands.entity(root).despawn_recursive();
-            }
+    let still_selected = |root: &TowerUIRoot| {
+        selections
+            .get(root.selected)
+            .is_ok_and(|(_, selection, _)| selection.selected())
+    };
+    let open: Vec<(Entity, &TowerUIRoot)> = roots.iter().collect();
+    if changed.is_empty() && open.len() <= 1 && open.iter().all(|(_, root)| still_selected(root)) {
+        return;
+    }
+    if open.len() > 1 {
+        warn!("Found {} build menus, keeping at most one", open.len());
+    }
+
+    let wanted = selections
+        .iter()
+        .find(|(_, selection, _)| selection.selected())
+        .map(|(entity, _, tower_type)| (entity, tower_type.copied()));
+    let wanted_root = wanted.map(|(selected, tower_type)| TowerUIRoot {
+        selected,
+        menu: match tower_type {
+            Some(_) => SlotMenu::Tower,
+            None => SlotMenu::Build,
+        },
+    });
+    let mut kept = false;
+    for (entity, root) in open {
+        if !kept && Some(*root) == wanted_root {
+            kept = true;
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    if kept {
+        return;
+    }
+    let layout = UiLayout::from_windows(&windows, settings.ui_scale);
+    match wanted {
+        Some((selected, None)) => create_ui(&mut commands, &asset_server, &layout, selected),
+        Some((tower, Some(tower_type))) => {
+            create_tower_menu(&mut commands, &asset_server, &layout, tower, tower_type)
         }
-        //No root exist
-        Err(QuerySingleError::NoEntities(..)) => {
-            if at_least_one_selected {
-                create_ui(&mut commands, &asset_server);
+        None => {}
+    }
+}
+
+/// Keeps the damage shown in the tower menu up to date, in the active locale.
+pub fn update_tower_info(
+    localizer: Localizer,
+    mut events: EventReader<AssetEvent<StringTable>>,
+    roots: Query<(&TowerUIRoot, ChangeTrackers<TowerUIRoot>)>,
+    towers: Query<(&TowerStats, ChangeTrackers<TowerStats>)>,
+    mut labels: Query<&mut Text, With<TowerInfoLabel>>,
+) {
+    let strings_changed = localizer.changed(&mut events);
+    for (root, root_tracker) in &roots {
+        let stats = match towers.get(root.selected) {
+            Ok((stats, tracker)) if tracker.is_changed() || root_tracker.is_added() || strings_changed => stats,
+            _ => continue,
+        };
+        let dps = localizer.get("tower-dps").replace("{dps}", &format!("{:.1}", stats.dps()));
+        for mut text in &mut labels {
+            if let Some(section) = text.sections.get_mut(1) {
+                section.value = format!("\n{}", dps);
             }
         }
-        _ => unreachable!("Too many ui tower roots!"),
     }
-}
\ No newline at end of file
+}
+
+type MenuImages = Or<(With<TowerButton>, With<TowerMenuImage>)>;
+type MenuLabels = Or<(With<TowerButtonLabel>, With<TowerInfoLabel>)>;
+
+pub fn resize_ui(
+    mut resized: EventReader<WindowResized>,
+    settings: Res<Settings>,
+    windows: Res<Windows>,
+    mut buttons: Query<&mut Style, MenuImages>,
+    mut labels: Query<&mut Text, MenuLabels>,
+) {
+    let window_resized = resized.iter().count() > 0;
+    if !window_resized && !settings.is_changed() {
+        return;
+    }
+    let layout = UiLayout::from_windows(&windows, settings.ui_scale);
+    for mut style in &mut buttons {
+        layout.apply(&mut style);
+    }
+    for mut text in &mut labels {
+        for section in &mut text.sections {
+            section.style.font_size = layout.font_size;
+        }
+    }
+}
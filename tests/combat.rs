@@ -0,0 +1,216 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_tower_defense::*;
+use common::Scenario;
+
+/// Where a tomato tower at the origin aims a bullet at a target standing two units away.
+const NEAR: Vec3 = Vec3::new(2.0, 0.6, 0.0);
+const FAR: Vec3 = Vec3::new(20.0, 0.6, 0.0);
+
+#[test]
+fn tower_fires_once_per_shot_interval() {
+    let mut scenario = Scenario::new();
+    let tower = scenario.tower(TowerType::Tomato, Vec3::ZERO);
+    scenario.target(FAR, 100, 0.0);
+
+    scenario.run_ticks(59);
+    assert_eq!(scenario.count::<With<Bullet>>(), 0);
+    scenario.step();
+    assert_eq!(scenario.count::<With<Bullet>>(), 1);
+    scenario.run_ticks(60);
+    assert_eq!(scenario.count::<With<Bullet>>(), 2);
+    assert_eq!(scenario.get::<TowerStats>(tower).shots_fired, 2);
+}
+
+#[test]
+fn tower_without_targets_holds_fire() {
+    let mut scenario = Scenario::new();
+    let tower = scenario.tower(TowerType::Potato, Vec3::ZERO);
+
+    scenario.run_ticks(240);
+    assert_eq!(scenario.count::<With<Bullet>>(), 0);
+    assert_eq!(scenario.get::<TowerStats>(tower).shots_fired, 0);
+}
+
+#[test]
+fn bullet_hit_damages_target_and_despawns_bullet() {
+    let mut scenario = Scenario::new();
+    let tower = scenario.tower(TowerType::Tomato, Vec3::ZERO);
+    let target = scenario.target(NEAR, 5, 0.0);
+
+    // Fired at the end of tick 60, then 1.6 units at 3.5 units per second to get within reach.
+    let hit = scenario.run_until(120, |scenario| scenario.get::<Health>(target).value < 5);
+    assert_eq!(hit, Some(88));
+    assert_eq!(scenario.get::<Health>(target).value, 4);
+    assert_eq!(scenario.count::<With<Bullet>>(), 0);
+    let stats = scenario.get::<TowerStats>(tower);
+    assert_eq!((stats.hits, stats.damage), (1, 1));
+}
+
+#[test]
+fn fast_bullet_cannot_skip_past_a_thin_target() {
+    let mut scenario = Scenario::new();
+    scenario.tower(TowerType::Potato, Vec3::ZERO);
+    let target = scenario.target(NEAR, 5, 0.0);
+    // Far thinner than the distance a potato travels in one tick.
+    scenario.app.world.get_mut::<Target>(target).unwrap().radius = 0.02;
+
+    // Potatoes are fired every two seconds.
+    let hit = scenario.run_until(240, |scenario| scenario.get::<Health>(target).value < 5);
+    assert!(hit.is_some());
+    assert_eq!(scenario.count::<With<Bullet>>(), 0);
+}
+
+#[test]
+fn larger_targets_are_hit_sooner() {
+    let mut scenario = Scenario::new();
+    scenario.tower(TowerType::Tomato, Vec3::ZERO);
+    let target = scenario.target(NEAR, 5, 0.0);
+    scenario.app.world.get_mut::<Target>(target).unwrap().radius = 1.0;
+
+    let hit = scenario.run_until(120, |scenario| scenario.get::<Health>(target).value < 5);
+    assert!(hit.unwrap() < 88);
+}
+
+#[test]
+fn target_dies_on_the_tick_its_health_runs_out() {
+    let mut scenario = Scenario::new();
+    scenario.tower(TowerType::Tomato, Vec3::ZERO);
+    let target = scenario.target(NEAR, 1, 0.0);
+
+    let died = scenario.run_until(120, |scenario| !scenario.exists(target));
+    assert_eq!(died, Some(88));
+    assert_eq!(scenario.player().gold, STARTING_GOLD + TARGET_BOUNTY);
+    assert_eq!(scenario.stats().waves[0].killed, 1);
+}
+
+#[test]
+fn target_out_of_health_dies_on_the_next_tick() {
+    let mut scenario = Scenario::new();
+    let target = scenario.target(FAR, 5, 0.0);
+    scenario.app.world.get_mut::<Health>(target).unwrap().value = 0;
+
+    scenario.step();
+    assert!(!scenario.exists(target));
+    assert_eq!(scenario.player().gold, STARTING_GOLD + TARGET_BOUNTY);
+}
+
+#[test]
+fn target_reaching_the_path_end_costs_a_life() {
+    let mut scenario = Scenario::new();
+    let path_end = Level::default().path_end;
+    let target = scenario.target(Vec3::new(path_end - 0.01, 0.4, 2.5), 5, 1.0);
+
+    scenario.run_ticks(2);
+    assert!(!scenario.exists(target));
+    assert_eq!(scenario.player().lives, STARTING_LIVES - 1);
+    assert_eq!(scenario.player().gold, STARTING_GOLD);
+    assert_eq!(scenario.stats().waves[0].leaked, 1);
+}
+
+#[test]
+fn target_dying_as_it_reaches_the_path_end_is_only_killed() {
+    let mut scenario = Scenario::new();
+    let path_end = Level::default().path_end;
+    let target = scenario.target(Vec3::new(path_end + 0.5, 0.4, 2.5), 0, 1.0);
+
+    scenario.step();
+    assert!(!scenario.exists(target));
+    assert_eq!(scenario.player().lives, STARTING_LIVES);
+    assert_eq!(scenario.player().gold, STARTING_GOLD + TARGET_BOUNTY);
+    assert_eq!(scenario.stats().waves[0].killed, 1);
+    assert_eq!(scenario.stats().waves[0].leaked, 0);
+}
+
+#[test]
+fn fight_leaves_no_entities_behind() {
+    let mut scenario = Scenario::new();
+    scenario.tower(TowerType::Cabbage, Vec3::ZERO);
+    let before = scenario.entity_count();
+    let target = scenario.target(NEAR, 3, 0.0);
+
+    assert!(scenario.run_until(3_000, |scenario| !scenario.exists(target)).is_some());
+    // Let any bullet still in flight run out its lifetime.
+    scenario.run_ticks(11 * TICK_RATE as u64);
+    assert_eq!(scenario.count::<With<Bullet>>(), 0);
+    // Spent bullets stay around, hidden, for the next shots.
+    let pooled = scenario.app.world.resource::<ProjectilePool>().metrics();
+    assert_eq!(pooled.idle, pooled.created as usize);
+    assert_eq!(scenario.entity_count(), before + pooled.created);
+}
+
+#[test]
+fn spent_bullets_are_fired_again() {
+    let mut scenario = Scenario::new();
+    let tower = scenario.tower(TowerType::Tomato, Vec3::ZERO);
+    scenario.target(NEAR, 100, 0.0);
+
+    // A tomato reaches the target in half a second, well before the next shot.
+    scenario.run_ticks(10 * TICK_RATE as u64);
+    let pool = scenario.app.world.resource::<ProjectilePool>().metrics();
+    assert_eq!((pool.created, pool.reused), (1, 9));
+    // Past the last hit, before the next shot.
+    scenario.run_ticks(30);
+    assert_eq!(scenario.get::<TowerStats>(tower).hits, 10);
+
+    let bullet = scenario
+        .app
+        .world
+        .query_filtered::<Entity, With<PooledBullet>>()
+        .single(&scenario.app.world);
+    assert!(!scenario.get::<Visibility>(bullet).is_visible);
+}
+
+#[test]
+fn bullet_outlives_the_tower_that_fired_it() {
+    let mut scenario = Scenario::new();
+    let tower = scenario.tower(TowerType::Tomato, Vec3::ZERO);
+    let target = scenario.target(NEAR, 5, 0.0);
+
+    scenario.run_ticks(60);
+    let bullet = scenario
+        .app
+        .world
+        .query_filtered::<Entity, With<Bullet>>()
+        .single(&scenario.app.world);
+    assert_eq!(scenario.get::<Bullet>(bullet).source, Some(tower));
+    despawn_with_children_recursive(&mut scenario.app.world, tower);
+
+    let hit = scenario.run_until(60, |scenario| scenario.get::<Health>(target).value < 5);
+    assert_eq!(hit, Some(88));
+}
+
+#[derive(Default)]
+struct Seen {
+    hits: Vec<DamageEvent>,
+    kills: Vec<TargetKilled>,
+}
+
+fn watch_combat(mut hits: EventReader<DamageEvent>, mut kills: EventReader<TargetKilled>, mut seen: ResMut<Seen>) {
+    seen.hits.extend(hits.iter().copied());
+    seen.kills.extend(kills.iter().copied());
+}
+
+#[test]
+fn hits_and_kills_are_announced() {
+    let mut scenario = Scenario::new();
+    scenario
+        .app
+        .init_resource::<Seen>()
+        .add_simulation_system(SimulationSet::Cleanup, watch_combat);
+    let tower = scenario.tower(TowerType::Tomato, Vec3::ZERO);
+    let target = scenario.target(NEAR, 2, 0.0);
+
+    assert!(scenario.run_until(300, |scenario| !scenario.exists(target)).is_some());
+    let seen = scenario.app.world.resource::<Seen>();
+    let hit = DamageEvent {
+        source_tower: Some(tower),
+        target,
+        amount: BULLET_DAMAGE,
+        kind: DamageKind::Bullet(TowerType::Tomato),
+    };
+    assert_eq!(seen.hits, vec![hit, hit]);
+    assert_eq!(seen.kills.len(), 1);
+    assert_eq!((seen.kills[0].entity, seen.kills[0].position), (target, NEAR));
+}
@@ -0,0 +1,155 @@
+//! Scenario harness: a headless game app that tests fill with towers and targets,
+//! step tick by tick and inspect.
+#![allow(dead_code)]
+
+use bevy::{
+    asset::AssetPlugin,
+    ecs::{query::WorldQuery, system::CommandQueue},
+    prelude::*,
+    window::WindowResized,
+};
+use bevy_tower_defense::*;
+
+pub struct Scenario {
+    pub app: App,
+}
+
+impl Scenario {
+    /// A game on an empty level: no build slots and no waves, so only what the test spawns exists.
+    pub fn new() -> Self {
+        Scenario::with_level(Level {
+            name: "scenario".to_string(),
+            slots: Vec::new(),
+            waves: Vec::new(),
+            ..Level::default()
+        })
+    }
+
+    pub fn with_level(level: Level) -> Self {
+        let mut app = App::new();
+        app.insert_resource(level)
+        .insert_resource(GameRng::new(0))
+        .add_plugin(HeadlessPlugin)
+        .add_startup_system(spawn_headless_scene);
+        // Run the startup stages now, so tests start from tick 0 with the player spawned.
+        app.world.resource_mut::<SimulationClock>().mode = ClockMode::Stepped(0);
+        app.update();
+        app.world.resource_mut::<SimulationClock>().mode = ClockMode::Stepped(1);
+        Scenario { app }
+    }
+
+    /// Adds what the build menu needs to run without a window.
+    pub fn with_ui(mut self) -> Self {
+        self.app
+            .add_plugin(AssetPlugin)
+            .insert_resource(Windows::default())
+            .add_event::<WindowResized>()
+            .insert_resource(Settings::default())
+            .insert_resource(Input::<KeyCode>::default())
+            .insert_resource(Input::<MouseButton>::default())
+            .add_plugin(LocalizationPlugin)
+            .add_plugin(TowerUiPlugin);
+        self
+    }
+
+    fn spawn<T>(&mut self, spawn: impl FnOnce(&mut Commands, &GameAssets) -> T) -> T {
+        let mut queue = CommandQueue::default();
+        let spawned = {
+            let mut commands = Commands::new(&mut queue, &self.app.world);
+            spawn(&mut commands, &GameAssets::placeholder())
+        };
+        queue.apply(&mut self.app.world);
+        spawned
+    }
+
+    pub fn tower(&mut self, tower_type: TowerType, position: Vec3) -> Entity {
+        self.spawn(|commands, assets| spawn_tower(commands, assets, position, tower_type))
+    }
+
+    pub fn target(&mut self, position: Vec3, health: i32, speed: f32) -> Entity {
+        let target = Target {
+            speed,
+            bounty: TARGET_BOUNTY,
+            wave: 0,
+            radius: TARGET_RADIUS,
+        };
+        self.spawn(|commands, assets| {
+            spawn_target(commands, assets, position, target, Health { value: health })
+        })
+    }
+
+    pub fn build_slot(&mut self, position: Vec3, slot: usize) -> Entity {
+        self.spawn(|commands, _| spawn_build_slot(commands, position, slot))
+    }
+
+    /// Runs one frame, which simulates exactly one tick.
+    pub fn step(&mut self) {
+        self.app.update();
+    }
+
+    pub fn run_ticks(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            self.step();
+        }
+    }
+
+    /// Steps until `done` holds, returning the tick it first held on, or `None` after `max_ticks`.
+    pub fn run_until(&mut self, max_ticks: u64, mut done: impl FnMut(&mut Scenario) -> bool) -> Option<u64> {
+        for _ in 0..max_ticks {
+            self.step();
+            if done(self) {
+                return Some(self.tick());
+            }
+        }
+        None
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.app.world.resource::<SimulationClock>().tick()
+    }
+
+    pub fn exists(&self, entity: Entity) -> bool {
+        self.app.world.get_entity(entity).is_some()
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> &T {
+        self.app
+            .world
+            .get::<T>(entity)
+            .unwrap_or_else(|| panic!("{:?} has no {}", entity, std::any::type_name::<T>()))
+    }
+
+    /// Number of entities matching the query filter `F`, e.g. `count::<With<Bullet>>()`.
+    pub fn count<F: WorldQuery>(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<Entity, F>()
+            .iter(&self.app.world)
+            .count()
+    }
+
+    pub fn entity_count(&self) -> u32 {
+        self.app.world.entities().len()
+    }
+
+    pub fn player(&self) -> Player {
+        *self.app.world.resource::<Player>()
+    }
+
+    pub fn stats(&self) -> GameStats {
+        self.app.world.resource::<GameStats>().clone()
+    }
+
+    pub fn act(&mut self, action: PlayerAction) {
+        self.app.world.resource_mut::<ActionQueue>().push(action);
+    }
+
+    /// Holds `key` down for one frame, then lets go.
+    pub fn press(&mut self, key: KeyCode) {
+        self.app.world.resource_mut::<Input<KeyCode>>().press(key);
+        self.step();
+        let mut keyboard = self.app.world.resource_mut::<Input<KeyCode>>();
+        keyboard.release(key);
+        keyboard.clear();
+    }
+}
@@ -0,0 +1,22 @@
+use bevy_tower_defense::*;
+
+#[test]
+fn presets_and_custom_multipliers_parse() {
+    assert_eq!("Hard".parse(), Ok(Difficulty::Hard));
+    assert_eq!(
+        "custom:health=1.5,gold=0.8".parse(),
+        Ok(Difficulty::Custom(DifficultyModifiers {
+            health: 1.5,
+            gold: 0.8,
+            ..DifficultyModifiers::default()
+        }))
+    );
+    assert_eq!("custom:".parse(), Ok(Difficulty::Custom(DifficultyModifiers::default())));
+}
+
+#[test]
+fn bad_custom_multipliers_are_rejected() {
+    for bad in ["custom:health", "custom:health=-1", "custom:armor=2", "custom:speed=fast", "extreme"] {
+        assert!(bad.parse::<Difficulty>().is_err(), "{} was accepted", bad);
+    }
+}
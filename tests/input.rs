@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+use bevy_tower_defense::*;
+
+#[test]
+fn binding_taken_by_another_action_is_refused() {
+    let mut bindings = InputBindings::default();
+    assert_eq!(
+        bindings.bind(Action::CameraBack, Binding::Key(KeyCode::W)),
+        Err(Action::CameraForward)
+    );
+    assert_eq!(bindings.get(Action::CameraBack), [Binding::Key(KeyCode::S)]);
+    assert!(bindings.conflicts().is_empty());
+
+    // Binding an action to what it already has changes nothing.
+    bindings.bind(Action::CameraForward, Binding::Key(KeyCode::W)).unwrap();
+    assert_eq!(bindings.get(Action::CameraForward), [Binding::Key(KeyCode::W)]);
+}
+
+#[test]
+fn unbound_binding_can_be_bound_to_another_action() {
+    let mut bindings = InputBindings::default();
+    let z = Binding::Key(KeyCode::Z);
+    bindings.bind(Action::CameraForward, z).unwrap();
+    assert_eq!(bindings.action_for(z), Some(Action::CameraForward));
+
+    bindings.unbind(Action::CameraForward, z);
+    assert_eq!(bindings.action_for(z), None);
+    assert_eq!(bindings.get(Action::CameraForward), [Binding::Key(KeyCode::W)]);
+
+    bindings.bind(Action::BuildTomato, z).unwrap();
+    assert_eq!(bindings.action_for(z), Some(Action::BuildTomato));
+}
@@ -0,0 +1,29 @@
+use bevy_tower_defense::*;
+
+#[test]
+fn level_without_slots_reports_the_empty_layout() {
+    let level = Level {
+        name: "no slots".to_string(),
+        slots: Vec::new(),
+        waves: vec![Wave {
+            count: 2,
+            ..Wave::default()
+        }],
+        ..Level::default()
+    };
+    let report = Optimizer {
+        level,
+        difficulty: Difficulty::Normal,
+        seed: 0,
+        budget: 150,
+        restarts: 2,
+        patience: 3,
+        max_seconds: 600.0,
+    }
+    .run(5);
+
+    assert_eq!(report.evaluations, 1);
+    assert_eq!(report.layouts.len(), 1);
+    assert!(report.layouts[0].towers.is_empty());
+    assert_eq!(report.layouts[0].score.leaked, 2);
+}
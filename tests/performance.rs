@@ -0,0 +1,93 @@
+mod common;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tower_defense::*;
+use common::Scenario;
+
+#[test]
+fn every_gameplay_system_is_timed_in_the_order_it_runs() {
+    let mut scenario = Scenario::new();
+    scenario.tower(TowerType::Tomato, Vec3::new(0.0, 0.8, 0.0));
+    scenario.target(Vec3::new(-2.0, 0.4, 2.5), 5, 0.6);
+    scenario.app.world.resource_mut::<SystemTimings>().take();
+
+    scenario.run_ticks(20);
+    let (ticks, systems) = scenario.app.world.resource_mut::<SystemTimings>().take();
+    assert_eq!(ticks, 20);
+    let names: Vec<&str> = systems.iter().map(|(_, name, _)| *name).collect();
+    for system in ["tower_shooting", "move_targets", "move_bullets", "bullet_collision", "apply_damage"] {
+        assert!(names.contains(&system), "{} is not timed: {:?}", system, names);
+    }
+    assert!(systems.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    assert!(systems.iter().any(|(_, _, time)| !time.is_zero()));
+
+    let (ticks, systems) = scenario.app.world.resource_mut::<SystemTimings>().take();
+    assert_eq!(ticks, 0);
+    assert!(systems.iter().all(|(_, _, time)| time.is_zero()));
+}
+
+#[test]
+fn frame_times_are_summarized_as_percentiles() {
+    let frames: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+    let report = PerformanceReport::default().with_frames(&frames, 2.0);
+    assert_eq!(report.frames, 100);
+    assert_eq!(report.fps, 50.0);
+    assert_eq!(report.frame_p50, 51.0);
+    assert_eq!(report.frame_p95, 95.0);
+    assert_eq!(report.frame_p99, 99.0);
+    assert_eq!(report.frame_max, 100.0);
+
+    let empty = PerformanceReport::default().with_frames(&[], 0.0);
+    assert_eq!((empty.frames, empty.fps, empty.frame_max), (0, 0.0, 0.0));
+}
+
+#[test]
+fn csv_rows_match_the_header() {
+    let report = PerformanceReport {
+        bullets: 3,
+        systems: vec![
+            (SimulationSet::Spawning, "index_targets", 1.5),
+            (SimulationSet::Collision, "index_targets", 2.0),
+        ],
+        ..default()
+    };
+    let header = report.csv_header();
+    assert!(header.ends_with("spawning_index_targets_us,collision_index_targets_us"));
+    assert_eq!(header.split(',').count(), report.csv_row().split(',').count());
+}
+
+#[test]
+fn csv_log_appends_to_earlier_runs_with_one_header() {
+    let path = std::env::temp_dir().join(format!("perf-csv-append-{}.csv", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let report = PerformanceReport::default();
+    for _ in 0..2 {
+        let mut log = PerformanceLog::new(false, Some(&path)).unwrap();
+        log.write(&report).unwrap();
+    }
+    let csv = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let header = report.csv_header();
+    let row = report.csv_row();
+    assert_eq!(csv.lines().collect::<Vec<_>>(), [header.as_str(), row.as_str(), row.as_str()]);
+}
+
+#[test]
+fn overlay_is_toggled_by_its_action() {
+    let mut scenario = Scenario::new().with_ui();
+    scenario.app.add_plugin(PerformanceOverlayPlugin);
+    let overlay = scenario
+        .app
+        .world
+        .spawn()
+        .insert(Visibility { is_visible: false })
+        .insert(PerformanceOverlay)
+        .id();
+
+    scenario.press(KeyCode::F3);
+    assert!(scenario.get::<Visibility>(overlay).is_visible);
+    scenario.press(KeyCode::F3);
+    assert!(!scenario.get::<Visibility>(overlay).is_visible);
+}
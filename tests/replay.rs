@@ -0,0 +1,75 @@
+mod common;
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_tower_defense::*;
+use common::Scenario;
+
+#[test]
+fn recorded_actions_are_written_out_on_exit() {
+    let path = std::env::temp_dir().join(format!("replay-on-exit-{}.ron", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut scenario = Scenario::new();
+    scenario.build_slot(Vec3::new(0.0, 0.8, 0.0), 0);
+    let recorder = ReplayRecorder::new(path.clone(), 0, Difficulty::Normal, Level::default());
+    scenario.app.insert_resource(recorder);
+
+    let build = PlayerAction::BuildTower {
+        slot: 0,
+        tower_type: TowerType::Tomato,
+    };
+    scenario.act(build);
+    scenario.step();
+    assert_eq!(scenario.count::<With<Tower>>(), 1);
+    // Recording stays in memory during the game.
+    assert!(!path.exists());
+
+    scenario.app.world.resource_mut::<Events<AppExit>>().send(AppExit);
+    scenario.step();
+    let replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replay.actions.len(), 1);
+    assert_eq!(replay.actions[0].action, build);
+}
+
+fn outcome(scenario: &Scenario) -> (Player, String, String) {
+    let world = &scenario.app.world;
+    (
+        scenario.player(),
+        ron::to_string(world.resource::<WaveState>()).unwrap(),
+        ron::to_string(world.resource::<GameStats>()).unwrap(),
+    )
+}
+
+#[test]
+fn played_back_replay_reproduces_the_recorded_game() {
+    let path = std::env::temp_dir().join(format!("replay-playback-{}.ron", std::process::id()));
+    let mut recorded = Scenario::with_level(Level::default());
+    let seed = recorded.app.world.resource::<GameRng>().seed();
+    let recorder = ReplayRecorder::new(path.clone(), seed, Difficulty::Normal, Level::default());
+    recorded.app.insert_resource(recorder);
+    let builds = [
+        (0, 4, TowerType::Tomato),
+        (300, 5, TowerType::Potato),
+        (2_000, 0, TowerType::Cabbage),
+    ];
+    for (tick, slot, tower_type) in builds {
+        recorded.run_ticks(tick - recorded.tick());
+        recorded.act(PlayerAction::BuildTower { slot, tower_type });
+    }
+    recorded.run_ticks(3_000 - recorded.tick());
+    let replay = recorded.app.world.resource::<ReplayRecorder>().replay().clone();
+    recorded.app.world.remove_resource::<ReplayRecorder>();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replay.actions.len(), builds.len());
+
+    let mut played = Scenario::with_level(replay.level.clone());
+    assert_eq!(played.app.world.resource::<GameRng>().seed(), replay.seed);
+    played
+        .app
+        .insert_resource(replay.difficulty)
+        .insert_resource(ReplayPlayback::new(&replay));
+    played.run_ticks(3_000);
+    assert!(played.app.world.resource::<ReplayPlayback>().is_finished());
+    assert_eq!(played.count::<With<Tower>>(), builds.len());
+    assert_eq!(outcome(&played), outcome(&recorded));
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_tower_defense::*;
+use common::Scenario;
+
+fn snapshot(scenario: &mut Scenario) -> String {
+    ron::to_string(&SavedGame::capture(&mut scenario.app.world)).unwrap()
+}
+
+fn game_in_progress() -> Scenario {
+    let mut scenario = Scenario::with_level(Level::default());
+    scenario.act(PlayerAction::BuildTower {
+        slot: 4,
+        tower_type: TowerType::Tomato,
+    });
+    scenario.act(PlayerAction::BuildTower {
+        slot: 5,
+        tower_type: TowerType::Potato,
+    });
+    scenario.run_ticks(1_000);
+    scenario
+}
+
+#[test]
+fn saved_game_keeps_everything_in_flight() {
+    let mut scenario = game_in_progress();
+    let saved = SavedGame::capture(&mut scenario.app.world);
+
+    assert_eq!(saved.tick, 1_000);
+    assert_eq!(saved.towers.len(), 2);
+    assert!(!saved.targets.is_empty());
+    assert!(!saved.bullets.is_empty());
+    assert_eq!(saved.player, scenario.player());
+}
+
+#[test]
+fn restored_game_plays_out_like_the_original() {
+    let mut original = game_in_progress();
+    let saved: SavedGame = ron::from_str(&snapshot(&mut original)).unwrap();
+
+    let mut restored = Scenario::new();
+    saved.restore(&mut restored.app.world);
+    assert_eq!(snapshot(&mut restored), snapshot(&mut original));
+
+    original.run_ticks(2_000);
+    restored.run_ticks(2_000);
+    assert_eq!(snapshot(&mut restored), snapshot(&mut original));
+}
+
+#[test]
+fn restored_game_leaves_free_slots_buildable() {
+    let mut original = game_in_progress();
+    let saved = SavedGame::capture(&mut original.app.world);
+    let mut restored = Scenario::new();
+    saved.restore(&mut restored.app.world);
+
+    restored.act(PlayerAction::BuildTower {
+        slot: 0,
+        tower_type: TowerType::Tomato,
+    });
+    restored.step();
+    assert_eq!(restored.count::<With<Tower>>(), 3);
+    assert_eq!(restored.count::<With<BuildSlot>>(), Level::default().slots.len());
+}
+
+#[test]
+fn save_from_another_version_is_rejected() {
+    let mut scenario = game_in_progress();
+    let mut saved = SavedGame::capture(&mut scenario.app.world);
+    saved.version = SAVE_VERSION + 1;
+    let path = std::env::temp_dir().join(format!("old-save-{}.ron", std::process::id()));
+    saved.save(&path).unwrap();
+
+    let error = SavedGame::load(&path).unwrap_err();
+    assert!(error.contains("not supported"), "{}", error);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn autosave_catches_a_one_tick_gap_between_waves() {
+    let path = std::env::temp_dir().join(format!("autosave-gap-{}.ron", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let wave = Wave {
+        count: 1,
+        speed: 20.0,
+        ..Wave::default()
+    };
+    let mut scenario = Scenario::with_level(Level {
+        waves: vec![wave.clone(), wave],
+        ..Level::default()
+    });
+    scenario
+        .app
+        .insert_resource(Autosave::new(path.clone()))
+        .add_system_to_stage(
+            SimulationStage,
+            autosave_between_waves.exclusive_system().at_end().after(TickEnd),
+        );
+    scenario.app.world.resource_mut::<SimulationClock>().mode = ClockMode::Stepped(MAX_TICKS_PER_FRAME);
+
+    scenario.run_until(1_000, |scenario| scenario.app.world.resource::<WaveState>().is_cleared());
+    let saved = SavedGame::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(saved.waves.next_wave(), 1);
+    assert!(saved.targets.is_empty());
+    assert_eq!(saved.stats.waves[0].leaked, 1);
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_tower_defense::*;
+use common::Scenario;
+
+#[derive(Default)]
+struct Ran(Vec<(SimulationSet, usize)>);
+
+fn probe(set: SimulationSet, index: usize) -> impl FnMut(ResMut<Ran>) {
+    move |mut ran: ResMut<Ran>| ran.0.push((set, index))
+}
+
+#[test]
+fn phases_run_in_order_whatever_order_systems_are_added_in() {
+    let mut scenario = Scenario::new();
+    scenario.app.init_resource::<Ran>();
+    for set in SimulationSet::ALL.into_iter().rev() {
+        for index in 0..3 {
+            scenario.app.add_simulation_system(set, probe(set, index));
+        }
+    }
+
+    scenario.step();
+    let expected: Vec<_> = SimulationSet::ALL
+        .into_iter()
+        .flat_map(|set| (0..3).map(move |index| (set, index)))
+        .collect();
+    assert_eq!(scenario.app.world.resource::<Ran>().0, expected);
+}
+
+#[test]
+fn no_target_outlives_a_tick_at_zero_health() {
+    let mut scenario = Scenario::new();
+    for (i, tower_type) in TowerType::ALL.into_iter().enumerate() {
+        scenario.tower(tower_type, Vec3::new(4.0 * i as f32, 0.8, 0.0));
+    }
+    for i in 0..10 {
+        scenario.target(Vec3::new(-2.0 * i as f32, 0.4, 2.5), 2, 0.6);
+    }
+
+    for _ in 0..60 * TICK_RATE {
+        scenario.step();
+        let mut targets = scenario.app.world.query::<&Health>();
+        assert!(targets.iter(&scenario.app.world).all(|health| health.value > 0));
+    }
+    assert!(scenario.stats().waves[0].killed > 0);
+}
+
+/// Plays the same actions on the default level for `ticks`, simulating `ticks_per_frame` at a time.
+fn play(ticks_per_frame: u32, ticks: u64) -> String {
+    let mut replay = Replay::new(0, Difficulty::Normal, Level::default());
+    let builds = [
+        (0, 4, TowerType::Tomato),
+        (301, 5, TowerType::Potato),
+        (1_203, 2, TowerType::Tomato),
+    ];
+    for (tick, slot, tower_type) in builds {
+        replay.actions.push(RecordedAction {
+            tick,
+            action: PlayerAction::BuildTower { slot, tower_type },
+        });
+    }
+    let mut scenario = Scenario::with_level(replay.level.clone());
+    scenario.app.insert_resource(ReplayPlayback::new(&replay));
+    scenario.app.world.resource_mut::<SimulationClock>().mode = ClockMode::Stepped(ticks_per_frame);
+    // One step runs one frame.
+    scenario.run_ticks(ticks / ticks_per_frame as u64);
+    assert_eq!(scenario.tick(), ticks);
+    assert_eq!(scenario.count::<With<Tower>>(), 3);
+    ron::to_string(&SavedGame::capture(&mut scenario.app.world)).unwrap()
+}
+
+#[test]
+fn outcome_does_not_depend_on_how_many_ticks_run_per_frame() {
+    assert_eq!(play(7, 2_100), play(1, 2_100));
+}
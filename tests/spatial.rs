@@ -0,0 +1,78 @@
+use bevy::{prelude::*, utils::FloatOrd};
+use bevy_tower_defense::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn random_targets(rng: &mut ChaCha8Rng, count: u32) -> Vec<(Entity, Vec3, f32)> {
+    (0..count)
+        .map(|id| {
+            let position = Vec3::new(rng.gen_range(-20.0..20.0), 0.4, rng.gen_range(-20.0..20.0));
+            (Entity::from_raw(id), position, rng.gen_range(0.1..1.5))
+        })
+        .collect()
+}
+
+#[test]
+fn grid_lookups_match_a_scan_over_every_target() {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let mut grid = TargetGrid::default();
+    for count in [0, 1, 5, 200] {
+        let targets = random_targets(&mut rng, count);
+        grid.rebuild(targets.iter().copied());
+        for _ in 0..200 {
+            let point = Vec3::new(rng.gen_range(-25.0..25.0), 0.6, rng.gen_range(-25.0..25.0));
+            let nearest = targets
+                .iter()
+                .min_by_key(|(_, position, _)| FloatOrd(Vec3::distance(*position, point)))
+                .map(|(entity, position, _)| (*entity, *position));
+            assert_eq!(grid.nearest(point), nearest);
+
+            let to = point + Vec3::new(rng.gen_range(-4.0..4.0), 0.0, rng.gen_range(-4.0..4.0));
+            let first = targets
+                .iter()
+                .filter_map(|(entity, position, radius)| {
+                    let time = sweep_sphere(point, to, *position, *radius)?;
+                    Some((FloatOrd(time), *entity, *position))
+                })
+                .min_by_key(|(time, _, _)| *time)
+                .map(|(_, entity, position)| (entity, position));
+            assert_eq!(grid.first_hit(point, to), first);
+        }
+    }
+}
+
+#[test]
+fn ties_go_to_the_first_target_in_query_order() {
+    let mut grid = TargetGrid::default();
+    let (a, b) = (Entity::from_raw(1), Entity::from_raw(0));
+    // Equally far from the origin, in different cells.
+    grid.rebuild([(a, Vec3::new(3.0, 0.0, 0.0), 4.0), (b, Vec3::new(-3.0, 0.0, 0.0), 4.0)]);
+    assert_eq!(grid.nearest(Vec3::ZERO).map(|(entity, _)| entity), Some(a));
+    assert_eq!(grid.first_hit(Vec3::ZERO, Vec3::ZERO).map(|(entity, _)| entity), Some(a));
+}
+
+#[test]
+fn far_away_targets_are_found() {
+    let mut grid = TargetGrid::default();
+    let (near, far) = (Entity::from_raw(0), Entity::from_raw(1));
+    grid.rebuild([(far, Vec3::new(9_000.0, 0.0, -7_000.0), 0.5)]);
+    assert_eq!(grid.nearest(Vec3::ZERO).map(|(entity, _)| entity), Some(far));
+
+    grid.rebuild([
+        (far, Vec3::new(9_000.0, 0.0, -7_000.0), 0.5),
+        (near, Vec3::new(8_990.0, 0.0, -6_950.0), 0.5),
+    ]);
+    assert_eq!(grid.nearest(Vec3::ZERO).map(|(entity, _)| entity), Some(near));
+    assert_eq!(grid.nearest(Vec3::new(9_001.0, 0.0, -7_001.0)).map(|(entity, _)| entity), Some(far));
+}
+
+#[test]
+fn sweep_finds_where_a_segment_enters_a_sphere() {
+    let center = Vec3::new(5.0, 0.0, 0.0);
+    assert_eq!(sweep_sphere(Vec3::ZERO, Vec3::X * 10.0, center, 1.0), Some(0.4));
+    assert_eq!(sweep_sphere(center, center, center, 1.0), Some(0.0));
+    // Passing by, stopping short and moving away.
+    assert_eq!(sweep_sphere(Vec3::Z * 2.0, Vec3::new(10.0, 0.0, 2.0), center, 1.0), None);
+    assert_eq!(sweep_sphere(Vec3::ZERO, Vec3::X * 3.0, center, 1.0), None);
+    assert_eq!(sweep_sphere(Vec3::X * 7.0, Vec3::X * 10.0, center, 1.0), None);
+}
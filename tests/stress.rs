@@ -0,0 +1,21 @@
+use bevy_tower_defense::*;
+
+#[test]
+fn stress_test_fills_every_slot_and_sends_the_whole_wave() {
+    let stress = StressTest {
+        targets: 150,
+        path_length: 40.0,
+        seconds: 3.0,
+        seed: 0,
+    };
+    let slots = stress.level().slots.len();
+    assert_eq!(slots, 22);
+
+    let report = stress.run();
+    assert_eq!(report.towers, slots);
+    assert_eq!(report.ticks, 3 * TICK_RATE as u64);
+    assert_eq!(report.peak_targets, 150);
+    assert!(report.peak_bullets > 0);
+    assert!(report.worst_frame >= report.average_frame);
+    assert!(report.systems.iter().any(|system| system.system == "tower_shooting"));
+}
@@ -0,0 +1,250 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_mod_picking::Selection;
+use bevy_tower_defense::*;
+use common::Scenario;
+
+fn selectable_slot(scenario: &mut Scenario, slot: usize) -> Entity {
+    let base = scenario.build_slot(Vec3::new(4.0 * slot as f32, 0.8, 0.0), slot);
+    scenario.app.world.entity_mut(base).insert(Selection::default());
+    base
+}
+
+fn select(scenario: &mut Scenario, entity: Entity, selected: bool) {
+    scenario
+        .app
+        .world
+        .get_mut::<Selection>(entity)
+        .unwrap()
+        .set_selected(selected);
+}
+
+#[test]
+fn build_menu_follows_the_selection() {
+    let mut scenario = Scenario::new().with_ui();
+    let base = selectable_slot(&mut scenario, 0);
+
+    scenario.step();
+    assert_eq!(scenario.count::<With<TowerUIRoot>>(), 0);
+
+    select(&mut scenario, base, true);
+    scenario.step();
+    assert_eq!(scenario.count::<With<TowerUIRoot>>(), 1);
+    assert_eq!(scenario.count::<With<TowerButton>>(), TowerType::ALL.len());
+
+    select(&mut scenario, base, false);
+    scenario.step();
+    assert_eq!(scenario.count::<With<TowerUIRoot>>(), 0);
+    assert_eq!(scenario.count::<With<TowerButton>>(), 0);
+}
+
+#[test]
+fn hotkey_builds_on_the_selected_slot() {
+    let mut scenario = Scenario::new().with_ui();
+    let base = selectable_slot(&mut scenario, 0);
+    let other = selectable_slot(&mut scenario, 1);
+    select(&mut scenario, base, true);
+    scenario.step();
+
+    scenario.press(KeyCode::Key2);
+    assert!(!scenario.exists(base));
+    assert!(scenario.exists(other));
+    let towers: Vec<(usize, TowerType)> = scenario
+        .app
+        .world
+        .query::<(&BuildSlot, &TowerType)>()
+        .iter(&scenario.app.world)
+        .map(|(slot, tower_type)| (slot.0, *tower_type))
+        .collect();
+    assert_eq!(towers, vec![(0, TowerType::Potato)]);
+    assert_eq!(scenario.player().gold, STARTING_GOLD - TowerType::Potato.cost());
+
+    // The selected base is gone, so the menu closes.
+    scenario.step();
+    assert_eq!(scenario.count::<With<TowerUIRoot>>(), 0);
+}
+
+#[test]
+fn clicking_a_button_builds_its_tower() {
+    let mut scenario = Scenario::new().with_ui();
+    let base = selectable_slot(&mut scenario, 3);
+    select(&mut scenario, base, true);
+    scenario.step();
+
+    let cabbage_button = scenario
+        .app
+        .world
+        .query::<(Entity, &TowerType, With<TowerButton>)>()
+        .iter(&scenario.app.world)
+        .find(|(_, tower_type, _)| **tower_type == TowerType::Cabbage)
+        .map(|(entity, _, _)| entity)
+        .unwrap();
+    scenario
+        .app
+        .world
+        .entity_mut(cabbage_button)
+        .insert(Interaction::Clicked);
+    scenario.step();
+
+    assert!(!scenario.exists(base));
+    assert_eq!(scenario.count::<With<Tower>>(), 1);
+    assert_eq!(scenario.player().gold, STARTING_GOLD - TowerType::Cabbage.cost());
+}
+
+#[test]
+fn unaffordable_tower_is_not_built() {
+    let mut scenario = Scenario::new().with_ui();
+    let base = selectable_slot(&mut scenario, 0);
+    scenario.app.world.resource_mut::<Player>().gold = TowerType::Cabbage.cost() - 1;
+    select(&mut scenario, base, true);
+    scenario.step();
+
+    scenario.press(KeyCode::Key3);
+    assert!(scenario.exists(base));
+    assert_eq!(scenario.count::<With<Tower>>(), 0);
+    assert_eq!(scenario.player().gold, TowerType::Cabbage.cost() - 1);
+}
+
+fn selectable_tower(scenario: &mut Scenario, slot: usize, tower_type: TowerType) -> Entity {
+    let tower = scenario.tower(tower_type, Vec3::new(4.0 * slot as f32, 0.8, 0.0));
+    scenario
+        .app
+        .world
+        .entity_mut(tower)
+        .insert(BuildSlot(slot))
+        .insert(Selection::default());
+    tower
+}
+
+fn open_menu(scenario: &mut Scenario) -> TowerUIRoot {
+    *scenario
+        .app
+        .world
+        .query::<&TowerUIRoot>()
+        .single(&scenario.app.world)
+}
+
+#[test]
+fn selected_tower_gets_its_own_menu() {
+    let mut scenario = Scenario::new().with_ui();
+    let base = selectable_slot(&mut scenario, 0);
+    let tower = selectable_tower(&mut scenario, 1, TowerType::Potato);
+
+    select(&mut scenario, tower, true);
+    scenario.step();
+    assert_eq!(
+        open_menu(&mut scenario),
+        TowerUIRoot {
+            selected: tower,
+            menu: SlotMenu::Tower
+        }
+    );
+    assert_eq!(scenario.count::<With<TowerButton>>(), 0);
+    assert_eq!(scenario.count::<With<TowerInfoLabel>>(), 1);
+
+    // Hotkeys only build on empty slots.
+    scenario.press(KeyCode::Key1);
+    assert_eq!(scenario.count::<With<Tower>>(), 1);
+    // The damage line comes from the English string table once it is loaded.
+    let localized = scenario.run_until(600, |scenario| {
+        let mut labels = scenario.app.world.query_filtered::<&Text, With<TowerInfoLabel>>();
+        labels.single(&scenario.app.world).sections[1].value == "\n0.0 DPS"
+    });
+    assert!(localized.is_some());
+
+    select(&mut scenario, tower, false);
+    select(&mut scenario, base, true);
+    scenario.step();
+    assert_eq!(
+        open_menu(&mut scenario),
+        TowerUIRoot {
+            selected: base,
+            menu: SlotMenu::Build
+        }
+    );
+    assert_eq!(scenario.count::<With<TowerInfoLabel>>(), 0);
+    assert_eq!(scenario.count::<With<TowerButton>>(), TowerType::ALL.len());
+}
+
+#[test]
+fn duplicate_menus_are_repaired() {
+    let mut scenario = Scenario::new().with_ui();
+    let base = selectable_slot(&mut scenario, 0);
+    select(&mut scenario, base, true);
+    scenario.step();
+    let root = open_menu(&mut scenario);
+    scenario.app.world.spawn().insert(root);
+    scenario.app.world.spawn().insert(root);
+
+    scenario.step();
+    assert_eq!(scenario.count::<With<TowerUIRoot>>(), 1);
+    assert_eq!(scenario.count::<With<TowerButton>>(), TowerType::ALL.len());
+}
+
+#[test]
+fn seed_label_follows_a_loaded_game() {
+    let mut scenario = Scenario::new().with_ui();
+    scenario.app.add_plugin(HudPlugin);
+    let label = scenario
+        .app
+        .world
+        .spawn()
+        .insert(Text::from_sections([
+            TextSection::new("seed-label", TextStyle::default()),
+            TextSection::new(": 0", TextStyle::default()),
+        ]))
+        .insert(SeedLabel)
+        .id();
+
+    scenario.app.world.insert_resource(GameRng::new(42));
+    scenario.step();
+    assert_eq!(scenario.get::<Text>(label).sections[1].value, ": 42");
+}
+
+#[test]
+fn tower_menu_art_resizes_with_the_buttons() {
+    let mut scenario = Scenario::new().with_ui();
+    let tower = selectable_tower(&mut scenario, 0, TowerType::Cabbage);
+    select(&mut scenario, tower, true);
+    scenario.step();
+
+    scenario.app.world.resource_mut::<Settings>().ui_scale = 2.0;
+    scenario.step();
+    let layout = UiLayout::from_windows(&Windows::default(), 2.0);
+    let size = scenario
+        .app
+        .world
+        .query_filtered::<&Style, With<TowerMenuImage>>()
+        .single(&scenario.app.world)
+        .size;
+    assert_eq!(size, Size::new(Val::Px(layout.button_size), Val::Px(layout.button_size)));
+}
+
+fn wait_for_text(scenario: &mut Scenario, entity: Entity, value: &str) -> bool {
+    scenario
+        .run_until(600, |scenario| scenario.get::<Text>(entity).sections[0].value == value)
+        .is_some()
+}
+
+#[test]
+fn language_key_switches_the_locale_of_shown_text() {
+    let mut scenario = Scenario::new().with_ui();
+    let label = scenario
+        .app
+        .world
+        .spawn()
+        .insert(Text::from_section("", TextStyle::default()))
+        .insert(Localized::new("tower-tomato"))
+        .id();
+    assert!(wait_for_text(&mut scenario, label, "Tomato Tower"));
+
+    scenario.press(KeyCode::F2);
+    assert_eq!(scenario.app.world.resource::<Locale>().0, "de");
+    assert!(wait_for_text(&mut scenario, label, "Tomatenturm"));
+
+    for _ in 1..LOCALES.len() {
+        scenario.press(KeyCode::F2);
+    }
+    assert_eq!(scenario.app.world.resource::<Locale>().0, DEFAULT_LOCALE);
+}